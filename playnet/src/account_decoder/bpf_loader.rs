@@ -0,0 +1,55 @@
+use serde_json::{json, Value};
+use solana_sdk::{
+    account::Account, bpf_loader_upgradeable::UpgradeableLoaderState, pubkey::Pubkey,
+};
+
+use crate::runtime::bank::PgBank;
+
+use super::AccountDecoder;
+
+/// Decodes the BPF upgradeable loader program, program data and buffer accounts
+pub struct BpfUpgradeableLoaderDecoder;
+
+impl AccountDecoder for BpfUpgradeableLoaderDecoder {
+    fn program(&self) -> &str {
+        "bpf-upgradeable-loader"
+    }
+
+    fn decode(&self, _: &Pubkey, account: &Account, _: &PgBank) -> Option<Value> {
+        let state = bincode::deserialize::<UpgradeableLoaderState>(&account.data).ok()?;
+        let encode_data = |offset: usize| {
+            json!([
+                base64::encode(account.data.get(offset..).unwrap_or_default()),
+                "base64"
+            ])
+        };
+
+        Some(match state {
+            UpgradeableLoaderState::Uninitialized => json!({ "type": "uninitialized" }),
+            UpgradeableLoaderState::Buffer { authority_address } => json!({
+                "type": "buffer",
+                "info": {
+                    "authority": authority_address.map(|authority| authority.to_string()),
+                    "data": encode_data(UpgradeableLoaderState::size_of_buffer_metadata()),
+                },
+            }),
+            UpgradeableLoaderState::Program {
+                programdata_address,
+            } => json!({
+                "type": "program",
+                "info": { "programData": programdata_address.to_string() },
+            }),
+            UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } => json!({
+                "type": "programData",
+                "info": {
+                    "slot": slot,
+                    "authority": upgrade_authority_address.map(|authority| authority.to_string()),
+                    "data": encode_data(UpgradeableLoaderState::size_of_programdata_metadata()),
+                },
+            }),
+        })
+    }
+}
@@ -0,0 +1,105 @@
+// Decodes account data into the `jsonParsed` representation of the Solana RPC.
+//
+// Decoders are registered per account owner. The builtin decoders cover the
+// accounts of the native programs, sysvars, SPL Token and SPL Token-2022, and
+// hosts can register their own decoders for the layouts of their programs.
+
+mod bpf_loader;
+mod nonce;
+mod stake;
+mod sysvar;
+mod token;
+mod vote;
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::{
+    account::Account, bpf_loader_upgradeable, pubkey::Pubkey, stake::program as stake_program,
+    system_program, sysvar as sysvar_program, vote::program as vote_program,
+};
+
+use crate::runtime::bank::PgBank;
+
+pub use token::{SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID};
+
+/// Decodes the data of the accounts owned by a program
+pub trait AccountDecoder: Send + Sync {
+    /// Name of the program in the parsed output, e.g. `spl-token`
+    fn program(&self) -> &str;
+
+    /// Returns `None` if the account data is not in a layout the decoder knows.
+    ///
+    /// `bank` can be used to look up the related accounts, e.g. the mint of a
+    /// token account.
+    fn decode(&self, pubkey: &Pubkey, account: &Account, bank: &PgBank) -> Option<Value>;
+}
+
+/// Decoded account data
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ParsedAccount {
+    pub program: String,
+    pub parsed: Value,
+    pub space: u64,
+}
+
+/// Account decoders by the owner of the accounts they decode
+pub struct AccountDecoders {
+    decoders: HashMap<Pubkey, Vec<Box<dyn AccountDecoder>>>,
+}
+
+impl Default for AccountDecoders {
+    fn default() -> Self {
+        let mut decoders = Self {
+            decoders: HashMap::new(),
+        };
+        decoders.register(system_program::id(), Box::new(nonce::NonceDecoder));
+        decoders.register(sysvar_program::id(), Box::new(sysvar::SysvarDecoder));
+        decoders.register(
+            bpf_loader_upgradeable::id(),
+            Box::new(bpf_loader::BpfUpgradeableLoaderDecoder),
+        );
+        decoders.register(
+            SPL_TOKEN_PROGRAM_ID,
+            Box::new(token::TokenDecoder::new("spl-token")),
+        );
+        decoders.register(
+            SPL_TOKEN_2022_PROGRAM_ID,
+            Box::new(token::TokenDecoder::new("spl-token-2022")),
+        );
+        decoders.register(stake_program::id(), Box::new(stake::StakeDecoder));
+        decoders.register(vote_program::id(), Box::new(vote::VoteDecoder));
+        decoders
+    }
+}
+
+impl AccountDecoders {
+    /// Registers the decoder for the accounts owned by `owner`.
+    ///
+    /// Decoders registered later take precedence over the existing decoders of
+    /// the same owner.
+    pub fn register(&mut self, owner: Pubkey, decoder: Box<dyn AccountDecoder>) {
+        self.decoders.entry(owner).or_default().push(decoder);
+    }
+
+    /// Returns `None` if none of the decoders of the owner can decode the account
+    pub fn decode(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+        bank: &PgBank,
+    ) -> Option<ParsedAccount> {
+        self.decoders
+            .get(&account.owner)?
+            .iter()
+            .rev()
+            .find_map(|decoder| {
+                Some(ParsedAccount {
+                    program: decoder.program().to_owned(),
+                    parsed: decoder.decode(pubkey, account, bank)?,
+                    space: account.data.len() as u64,
+                })
+            })
+    }
+}
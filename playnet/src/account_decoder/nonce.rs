@@ -0,0 +1,40 @@
+use serde_json::{json, Value};
+use solana_sdk::{
+    account::Account,
+    nonce::state::{State, Versions},
+    pubkey::Pubkey,
+};
+
+use crate::runtime::bank::PgBank;
+
+use super::AccountDecoder;
+
+/// Decodes the nonce accounts, the only system program accounts with data
+pub struct NonceDecoder;
+
+impl AccountDecoder for NonceDecoder {
+    fn program(&self) -> &str {
+        "nonce"
+    }
+
+    fn decode(&self, _: &Pubkey, account: &Account, _: &PgBank) -> Option<Value> {
+        if account.data.len() != State::size() {
+            return None;
+        }
+
+        let versions = bincode::deserialize::<Versions>(&account.data).ok()?;
+        Some(match versions.state() {
+            State::Uninitialized => json!({ "type": "uninitialized" }),
+            State::Initialized(data) => json!({
+                "type": "initialized",
+                "info": {
+                    "authority": data.authority.to_string(),
+                    "blockhash": data.blockhash().to_string(),
+                    "feeCalculator": {
+                        "lamportsPerSignature": data.fee_calculator.lamports_per_signature.to_string(),
+                    },
+                },
+            }),
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use serde_json::{json, Value};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    stake::state::{Meta, Stake, StakeState},
+};
+
+use crate::runtime::bank::PgBank;
+
+use super::AccountDecoder;
+
+/// Decodes the stake accounts
+pub struct StakeDecoder;
+
+impl AccountDecoder for StakeDecoder {
+    fn program(&self) -> &str {
+        "stake"
+    }
+
+    fn decode(&self, _: &Pubkey, account: &Account, _: &PgBank) -> Option<Value> {
+        Some(
+            match bincode::deserialize::<StakeState>(&account.data).ok()? {
+                StakeState::Uninitialized => json!({ "type": "uninitialized" }),
+                StakeState::Initialized(meta) => json!({
+                    "type": "initialized",
+                    "info": { "meta": meta_to_json(&meta), "stake": null },
+                }),
+                StakeState::Stake(meta, stake) => json!({
+                    "type": "delegated",
+                    "info": { "meta": meta_to_json(&meta), "stake": stake_to_json(&stake) },
+                }),
+                StakeState::RewardsPool => json!({ "type": "rewardsPool" }),
+            },
+        )
+    }
+}
+
+fn meta_to_json(meta: &Meta) -> Value {
+    json!({
+        "rentExemptReserve": meta.rent_exempt_reserve.to_string(),
+        "authorized": {
+            "staker": meta.authorized.staker.to_string(),
+            "withdrawer": meta.authorized.withdrawer.to_string(),
+        },
+        "lockup": {
+            "unixTimestamp": meta.lockup.unix_timestamp,
+            "epoch": meta.lockup.epoch,
+            "custodian": meta.lockup.custodian.to_string(),
+        },
+    })
+}
+
+fn stake_to_json(stake: &Stake) -> Value {
+    let delegation = &stake.delegation;
+    json!({
+        "delegation": {
+            "voter": delegation.voter_pubkey.to_string(),
+            "stake": delegation.stake.to_string(),
+            "activationEpoch": delegation.activation_epoch.to_string(),
+            "deactivationEpoch": delegation.deactivation_epoch.to_string(),
+            "warmupCooldownRate": delegation.warmup_cooldown_rate,
+        },
+        "creditsObserved": stake.credits_observed,
+    })
+}
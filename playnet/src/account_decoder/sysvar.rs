@@ -0,0 +1,118 @@
+use serde_json::{json, Value};
+use solana_sdk::{
+    account::Account, clock::Clock, epoch_schedule::EpochSchedule, pubkey::Pubkey, rent::Rent,
+    slot_hashes::SlotHashes, stake_history::StakeHistory, sysvar,
+};
+
+#[allow(deprecated)]
+use solana_sdk::sysvar::{fees::Fees, recent_blockhashes::RecentBlockhashes};
+
+use crate::runtime::bank::PgBank;
+
+use super::AccountDecoder;
+
+/// Decodes the sysvar accounts, the layout is picked by the sysvar address
+pub struct SysvarDecoder;
+
+impl AccountDecoder for SysvarDecoder {
+    fn program(&self) -> &str {
+        "sysvar"
+    }
+
+    #[allow(deprecated)]
+    fn decode(&self, pubkey: &Pubkey, account: &Account, _: &PgBank) -> Option<Value> {
+        let data = &account.data;
+        let (sysvar_type, info) = if *pubkey == sysvar::clock::id() {
+            let clock = bincode::deserialize::<Clock>(data).ok()?;
+            (
+                "clock",
+                json!({
+                    "slot": clock.slot,
+                    "epoch": clock.epoch,
+                    "epochStartTimestamp": clock.epoch_start_timestamp,
+                    "leaderScheduleEpoch": clock.leader_schedule_epoch,
+                    "unixTimestamp": clock.unix_timestamp,
+                }),
+            )
+        } else if *pubkey == sysvar::epoch_schedule::id() {
+            let schedule = bincode::deserialize::<EpochSchedule>(data).ok()?;
+            (
+                "epochSchedule",
+                json!({
+                    "slotsPerEpoch": schedule.slots_per_epoch,
+                    "leaderScheduleSlotOffset": schedule.leader_schedule_slot_offset,
+                    "warmup": schedule.warmup,
+                    "firstNormalEpoch": schedule.first_normal_epoch,
+                    "firstNormalSlot": schedule.first_normal_slot,
+                }),
+            )
+        } else if *pubkey == sysvar::fees::id() {
+            let fees = bincode::deserialize::<Fees>(data).ok()?;
+            (
+                "fees",
+                json!({
+                    "feeCalculator": {
+                        "lamportsPerSignature": fees.fee_calculator.lamports_per_signature.to_string(),
+                    },
+                }),
+            )
+        } else if *pubkey == sysvar::recent_blockhashes::id() {
+            let recent_blockhashes = bincode::deserialize::<RecentBlockhashes>(data).ok()?;
+            (
+                "recentBlockhashes",
+                recent_blockhashes
+                    .iter()
+                    .map(|entry| {
+                        json!({
+                            "blockhash": entry.blockhash.to_string(),
+                            "feeCalculator": {
+                                "lamportsPerSignature": entry.fee_calculator.lamports_per_signature.to_string(),
+                            },
+                        })
+                    })
+                    .collect(),
+            )
+        } else if *pubkey == sysvar::rent::id() {
+            let rent = bincode::deserialize::<Rent>(data).ok()?;
+            (
+                "rent",
+                json!({
+                    "lamportsPerByteYear": rent.lamports_per_byte_year.to_string(),
+                    "exemptionThreshold": rent.exemption_threshold,
+                    "burnPercent": rent.burn_percent,
+                }),
+            )
+        } else if *pubkey == sysvar::slot_hashes::id() {
+            let slot_hashes = bincode::deserialize::<SlotHashes>(data).ok()?;
+            (
+                "slotHashes",
+                slot_hashes
+                    .iter()
+                    .map(|(slot, hash)| json!({ "slot": slot, "hash": hash.to_string() }))
+                    .collect(),
+            )
+        } else if *pubkey == sysvar::stake_history::id() {
+            let stake_history = bincode::deserialize::<StakeHistory>(data).ok()?;
+            (
+                "stakeHistory",
+                stake_history
+                    .iter()
+                    .map(|(epoch, entry)| {
+                        json!({
+                            "epoch": epoch,
+                            "stakeHistory": {
+                                "effective": entry.effective,
+                                "activating": entry.activating,
+                                "deactivating": entry.deactivating,
+                            },
+                        })
+                    })
+                    .collect(),
+            )
+        } else {
+            return None;
+        };
+
+        Some(json!({ "type": sysvar_type, "info": info }))
+    }
+}
@@ -0,0 +1,174 @@
+// SPL Token and SPL Token-2022 accounts.
+//
+// The layouts are decoded by hand to not depend on the token program crates.
+// Token-2022 extensions are not decoded, only the base state of the accounts.
+
+use serde_json::{json, Value};
+use solana_sdk::{account::Account, pubkey, pubkey::Pubkey};
+
+use crate::runtime::bank::PgBank;
+
+use super::AccountDecoder;
+
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const SPL_TOKEN_2022_PROGRAM_ID: Pubkey =
+    pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+const ACCOUNT_LEN: usize = 165;
+const MINT_LEN: usize = 82;
+const MULTISIG_LEN: usize = 355;
+
+/// Token-2022 account type, stored right after the base account length
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Decodes the mint, token and multisig accounts of a token program
+pub struct TokenDecoder {
+    program: &'static str,
+}
+
+impl TokenDecoder {
+    pub fn new(program: &'static str) -> Self {
+        Self { program }
+    }
+}
+
+impl AccountDecoder for TokenDecoder {
+    fn program(&self) -> &str {
+        self.program
+    }
+
+    fn decode(&self, _: &Pubkey, account: &Account, bank: &PgBank) -> Option<Value> {
+        let data = &account.data;
+        match data.len() {
+            ACCOUNT_LEN => decode_account(data, account, bank),
+            MINT_LEN => decode_mint(data),
+            MULTISIG_LEN => decode_multisig(data),
+            len if len > ACCOUNT_LEN => match data[ACCOUNT_LEN] {
+                ACCOUNT_TYPE_MINT => decode_mint(&data[..MINT_LEN]),
+                ACCOUNT_TYPE_ACCOUNT => decode_account(&data[..ACCOUNT_LEN], account, bank),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+fn decode_account(data: &[u8], account: &Account, bank: &PgBank) -> Option<Value> {
+    let mint = read_pubkey(data, 0);
+    let state = match data[108] {
+        1 => "initialized",
+        2 => "frozen",
+        _ => return None,
+    };
+
+    // The amounts are displayed with the decimals of the mint
+    let decimals = bank
+        .get_account(&mint)
+        .filter(|mint_account| mint_account.owner == account.owner)
+        .and_then(|mint_account| match mint_account.data.len() {
+            MINT_LEN => Some(mint_account.data[44]),
+            len if len > ACCOUNT_LEN && mint_account.data[ACCOUNT_LEN] == ACCOUNT_TYPE_MINT => {
+                Some(mint_account.data[44])
+            }
+            _ => None,
+        })?;
+
+    let mut info = json!({
+        "mint": mint.to_string(),
+        "owner": read_pubkey(data, 32).to_string(),
+        "tokenAmount": token_amount(read_u64(data, 64), decimals),
+        "state": state,
+        "isNative": false,
+    });
+    if let Some(delegate) = read_option_pubkey(data, 72) {
+        info["delegate"] = json!(delegate.to_string());
+        info["delegatedAmount"] = token_amount(read_u64(data, 121), decimals);
+    }
+    if read_u32(data, 109) == 1 {
+        info["isNative"] = json!(true);
+        info["rentExemptReserve"] = token_amount(read_u64(data, 113), decimals);
+    }
+    if let Some(close_authority) = read_option_pubkey(data, 129) {
+        info["closeAuthority"] = json!(close_authority.to_string());
+    }
+
+    Some(json!({ "type": "account", "info": info }))
+}
+
+fn decode_mint(data: &[u8]) -> Option<Value> {
+    let is_initialized = data[45] == 1;
+    if !is_initialized {
+        return None;
+    }
+
+    Some(json!({
+        "type": "mint",
+        "info": {
+            "mintAuthority": read_option_pubkey(data, 0).map(|authority| authority.to_string()),
+            "supply": read_u64(data, 36).to_string(),
+            "decimals": data[44],
+            "isInitialized": is_initialized,
+            "freezeAuthority": read_option_pubkey(data, 46).map(|authority| authority.to_string()),
+        },
+    }))
+}
+
+fn decode_multisig(data: &[u8]) -> Option<Value> {
+    let (num_required_signers, num_valid_signers, is_initialized) = (data[0], data[1], data[2]);
+    if is_initialized != 1 {
+        return None;
+    }
+
+    let signers = (0..num_valid_signers.min(11) as usize)
+        .map(|i| read_pubkey(data, 3 + i * 32).to_string())
+        .collect::<Vec<_>>();
+    Some(json!({
+        "type": "multisig",
+        "info": {
+            "numRequiredSigners": num_required_signers,
+            "numValidSigners": num_valid_signers,
+            "isInitialized": true,
+            "signers": signers,
+        },
+    }))
+}
+
+fn token_amount(amount: u64, decimals: u8) -> Value {
+    let ui_amount_string = real_number_string_trimmed(amount, decimals);
+    json!({
+        "amount": amount.to_string(),
+        "decimals": decimals,
+        "uiAmount": ui_amount_string.parse::<f64>().ok(),
+        "uiAmountString": ui_amount_string,
+    })
+}
+
+/// Formats the amount with the decimal point, without the trailing zeros
+fn real_number_string_trimmed(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return amount.to_string();
+    }
+
+    let mut s = format!("{:01$}", amount, decimals + 1);
+    s.insert(s.len() - decimals, '.');
+    s.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Reads a `COption<Pubkey>`, a 4 byte tag followed by the pubkey
+fn read_option_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    (read_u32(data, offset) == 1).then(|| read_pubkey(data, offset + 4))
+}
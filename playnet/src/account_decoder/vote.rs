@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::{
+    account::Account,
+    clock::{Epoch, Slot, UnixTimestamp},
+    pubkey::Pubkey,
+};
+
+use crate::runtime::bank::PgBank;
+
+use super::AccountDecoder;
+
+/// Decodes the vote accounts
+pub struct VoteDecoder;
+
+impl AccountDecoder for VoteDecoder {
+    fn program(&self) -> &str {
+        "vote"
+    }
+
+    fn decode(&self, _: &Pubkey, account: &Account, _: &PgBank) -> Option<Value> {
+        let state = match bincode::deserialize::<VoteStateVersions>(&account.data).ok()? {
+            VoteStateVersions::V0_23_5 => return None,
+            VoteStateVersions::Current(state) => state,
+        };
+
+        Some(json!({
+            "type": "vote",
+            "info": {
+                "nodePubkey": state.node_pubkey.to_string(),
+                "authorizedWithdrawer": state.authorized_withdrawer.to_string(),
+                "commission": state.commission,
+                "votes": state
+                    .votes
+                    .iter()
+                    .map(|(slot, confirmation_count)| {
+                        json!({ "slot": slot, "confirmationCount": confirmation_count })
+                    })
+                    .collect::<Vec<_>>(),
+                "rootSlot": state.root_slot,
+                "authorizedVoters": state
+                    .authorized_voters
+                    .iter()
+                    .map(|(epoch, voter)| {
+                        json!({ "epoch": epoch, "authorizedVoter": voter.to_string() })
+                    })
+                    .collect::<Vec<_>>(),
+                "priorVoters": state
+                    .prior_voters
+                    .buf
+                    .iter()
+                    .filter(|(pubkey, ..)| *pubkey != Pubkey::default())
+                    .map(|(pubkey, epoch_of_last_authorized_switch, target_epoch)| {
+                        json!({
+                            "authorizedPubkey": pubkey.to_string(),
+                            "epochOfLastAuthorizedSwitch": epoch_of_last_authorized_switch,
+                            "targetEpoch": target_epoch,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+                "epochCredits": state
+                    .epoch_credits
+                    .iter()
+                    .map(|(epoch, credits, previous_credits)| {
+                        json!({
+                            "epoch": epoch,
+                            "credits": credits.to_string(),
+                            "previousCredits": previous_credits.to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+                "lastTimestamp": {
+                    "slot": state.last_timestamp.slot,
+                    "timestamp": state.last_timestamp.timestamp,
+                },
+            },
+        }))
+    }
+}
+
+/// Serialized layout of the vote program 1.14 `VoteStateVersions`.
+///
+/// The vote program is not a dependency of the runtime, the accounts are only
+/// decoded for display.
+#[derive(Deserialize)]
+enum VoteStateVersions {
+    /// Accounts of this version don't exist on the cluster anymore
+    V0_23_5,
+    Current(Box<VoteState>),
+}
+
+#[derive(Deserialize)]
+struct VoteState {
+    node_pubkey: Pubkey,
+    authorized_withdrawer: Pubkey,
+    commission: u8,
+    /// `(slot, confirmation_count)` of the lockouts
+    votes: VecDeque<(Slot, u32)>,
+    root_slot: Option<Slot>,
+    authorized_voters: BTreeMap<Epoch, Pubkey>,
+    prior_voters: PriorVoters,
+    /// `(epoch, credits, previous_credits)`
+    epoch_credits: Vec<(Epoch, u64, u64)>,
+    last_timestamp: BlockTimestamp,
+}
+
+#[derive(Deserialize)]
+struct PriorVoters {
+    /// `(pubkey, epoch_of_last_authorized_switch, target_epoch)`
+    buf: [(Pubkey, Epoch, Epoch); 32],
+    #[allow(dead_code)]
+    idx: usize,
+    #[allow(dead_code)]
+    is_empty: bool,
+}
+
+#[derive(Deserialize)]
+struct BlockTimestamp {
+    slot: Slot,
+    timestamp: UnixTimestamp,
+}
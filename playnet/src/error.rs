@@ -0,0 +1,114 @@
+// Errors returned from the public Playnet APIs.
+//
+// Errors get converted to JS exceptions at the WASM boundary(see `wasm`) rather
+// than panicking, which would kill the WASM instance.
+
+use std::io;
+
+use solana_sdk::{
+    clock::Slot,
+    message::SanitizeMessageError,
+    native_token::lamports_to_sol,
+    pubkey::{ParsePubkeyError, Pubkey},
+    signature::ParseSignatureError,
+    transaction::TransactionError,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PlaynetError {
+    /// The public key couldn't be parsed from the given string
+    #[error("Invalid public key `{0}`: {1}")]
+    InvalidPubkey(String, ParsePubkeyError),
+
+    /// The signature couldn't be parsed from the given string
+    #[error("Invalid signature `{0}`: {1}")]
+    InvalidSignature(String, ParseSignatureError),
+
+    /// A parameter is not in the expected format
+    #[error("Invalid param: {0}")]
+    InvalidParam(String),
+
+    /// The message failed sanitization
+    #[error("Invalid message: {0}")]
+    InvalidMessage(#[from] SanitizeMessageError),
+
+    /// The transaction is not in the wire format or doesn't fit in a packet.
+    ///
+    /// The messages are the same as the ones of the validator RPC.
+    #[error("{0}")]
+    InvalidWireTransaction(String),
+
+    /// The account dump is not in the `solana account --output json` format
+    #[error("Invalid account dump: {0}")]
+    InvalidAccountDump(String),
+
+    /// The snapshot is corrupted or was created by an unsupported version
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Bincode error: {0}")]
+    Bincode(#[from] bincode::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The transaction doesn't exist in the bank
+    #[error("Transaction not found")]
+    TransactionNotFound,
+
+    /// The bank is behind the minimum slot the request can be evaluated at
+    #[error("Minimum context slot has not been reached")]
+    MinContextSlotNotReached { context_slot: Slot },
+
+    /// A precompile instruction of the transaction failed verification
+    #[error("Transaction precompile verification failure {0:?}")]
+    PrecompileVerificationFailure(TransactionError),
+
+    /// The block doesn't exist in the bank
+    #[error("Block not found")]
+    BlockNotFound,
+
+    /// The airdrop is over the per request limit of the faucet
+    #[error(
+        "request too large; req: ◎{}, cap: ◎{}",
+        lamports_to_sol(*lamports),
+        lamports_to_sol(*cap)
+    )]
+    AirdropRequestTooLarge { lamports: u64, cap: u64 },
+
+    /// The account would receive more than the per account limit of the faucet
+    #[error(
+        "limit reached; req: ◎{}, to: {}, current: ◎{}, cap: ◎{}",
+        lamports_to_sol(*lamports),
+        to,
+        lamports_to_sol(*current),
+        lamports_to_sol(*cap)
+    )]
+    AirdropLimitReached {
+        lamports: u64,
+        to: Pubkey,
+        current: u64,
+        cap: u64,
+    },
+
+    /// The faucet can't pay for the airdrop
+    #[error(
+        "faucet exhausted; req: ◎{}, balance: ◎{}",
+        lamports_to_sol(*lamports),
+        lamports_to_sol(*balance)
+    )]
+    FaucetExhausted { lamports: u64, balance: u64 },
+
+    /// The airdrop transaction failed
+    #[error("airdrop transaction failed: {0}")]
+    AirdropFailed(TransactionError),
+
+    #[error(transparent)]
+    Transaction(#[from] TransactionError),
+}
+
+pub type Result<T> = std::result::Result<T, PlaynetError>;
@@ -1,11 +1,17 @@
 // Project structure:
-// ./playnet -> Starting point. Lifecycle starts when a Playnet instance gets created.
+// ./account_decoder -> `jsonParsed` decoding of the account data.
 // ./runtime -> Where all internal logic for Playnet runtime lives.
 // ./rpc     -> Methods for clients to interact with the Playnet.
+// ./pubsub  -> Subscriptions to the Playnet state changes.
+// ./wasm    -> JS bindings. Lifecycle starts when a Playnet instance gets created.
 
-mod playnet;
-mod rpc;
+pub mod account_decoder;
+pub mod error;
+pub mod pubsub;
+pub mod rpc;
 pub mod runtime;
 mod serde;
-mod types;
-mod utils;
+pub mod types;
+pub mod utils;
+#[cfg(feature = "wasm")]
+mod wasm;
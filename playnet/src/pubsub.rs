@@ -0,0 +1,369 @@
+// Subscriptions to the bank state changes, the equivalent of the Solana PubSub
+// `*Subscribe` methods.
+//
+// `PubSub` observes the bank and delivers the notifications to the sink of each
+// matching subscription. Sinks are called while the bank is locked, so hosts
+// should only queue the notifications in them and deliver them later.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, signature::Signature, slot_history::Slot,
+    transaction::TransactionError,
+};
+
+use crate::{
+    runtime::events::{BankEvent, BankObserver},
+    types::{AccountFilter, CommitmentLevel},
+};
+
+pub type SubscriptionId = u64;
+
+/// What to get notified of
+#[derive(Clone, Debug)]
+pub enum SubscriptionParams {
+    /// Changes of the account
+    Account(Pubkey),
+    /// Changes of the accounts owned by the program that match all the filters
+    Program {
+        program_id: Pubkey,
+        filters: Vec<AccountFilter>,
+    },
+    /// Commit of the transaction once its slot reaches the commitment level,
+    /// the subscription ends after the notification
+    Signature {
+        signature: Signature,
+        commitment: CommitmentLevel,
+    },
+    /// Logs of the committed transactions
+    Logs(LogsFilter),
+    /// Processed slots
+    Slot,
+}
+
+#[derive(Clone, Debug)]
+pub enum LogsFilter {
+    /// All transactions
+    All,
+    /// Transactions that mention the account
+    Mentions(Pubkey),
+}
+
+#[derive(Clone, Debug)]
+pub enum Notification {
+    Account {
+        account: Account,
+        slot: Slot,
+    },
+    Program {
+        pubkey: Pubkey,
+        account: Account,
+        slot: Slot,
+    },
+    Signature {
+        err: Option<TransactionError>,
+        slot: Slot,
+    },
+    Logs {
+        signature: Signature,
+        err: Option<TransactionError>,
+        logs: Vec<String>,
+        slot: Slot,
+    },
+    Slot {
+        slot: Slot,
+        parent: Slot,
+        root: Slot,
+    },
+}
+
+/// Receives the notifications of a subscription
+pub type NotificationSink = Box<dyn Fn(SubscriptionId, Notification) + Send + Sync>;
+
+struct Subscription {
+    params: SubscriptionParams,
+    sink: NotificationSink,
+    /// Slot and error of the committed transaction of a signature subscription
+    /// that is waiting for the slot to reach the commitment level
+    committed_tx: Option<(Slot, Option<TransactionError>)>,
+}
+
+#[derive(Default)]
+pub struct PubSub {
+    next_id: AtomicU64,
+    subscriptions: RwLock<HashMap<SubscriptionId, Subscription>>,
+}
+
+impl PubSub {
+    pub fn subscribe(&self, params: SubscriptionParams, sink: NotificationSink) -> SubscriptionId {
+        self.insert(params, sink, None)
+    }
+
+    /// Subscribes to the signature of a transaction that is already committed
+    /// in `slot` but hasn't reached the commitment level yet
+    pub(crate) fn subscribe_committed_tx(
+        &self,
+        params: SubscriptionParams,
+        sink: NotificationSink,
+        slot: Slot,
+        err: Option<TransactionError>,
+    ) -> SubscriptionId {
+        self.insert(params, sink, Some((slot, err)))
+    }
+
+    /// Returns `false` if the subscription doesn't exist
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.subscriptions.write().unwrap().remove(&id).is_some()
+    }
+
+    pub(crate) fn next_id(&self) -> SubscriptionId {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn insert(
+        &self,
+        params: SubscriptionParams,
+        sink: NotificationSink,
+        committed_tx: Option<(Slot, Option<TransactionError>)>,
+    ) -> SubscriptionId {
+        let id = self.next_id();
+        self.subscriptions.write().unwrap().insert(
+            id,
+            Subscription {
+                params,
+                sink,
+                committed_tx,
+            },
+        );
+        id
+    }
+}
+
+impl BankObserver for PubSub {
+    fn on_event(&self, event: &BankEvent) {
+        let mut finished = vec![];
+        let mut committed = vec![];
+        {
+            let subscriptions = self.subscriptions.read().unwrap();
+            for (id, subscription) in subscriptions.iter() {
+                let notification = match (&subscription.params, event) {
+                    (
+                        SubscriptionParams::Account(pubkey),
+                        BankEvent::AccountChanged {
+                            pubkey: changed,
+                            account,
+                            slot,
+                        },
+                    ) if pubkey == *changed => Notification::Account {
+                        account: (*account).clone(),
+                        slot: *slot,
+                    },
+                    (
+                        SubscriptionParams::Program {
+                            program_id,
+                            filters,
+                        },
+                        BankEvent::AccountChanged {
+                            pubkey,
+                            account,
+                            slot,
+                        },
+                    ) if account.owner == *program_id
+                        && filters.iter().all(|filter| filter.matches(account)) =>
+                    {
+                        Notification::Program {
+                            pubkey: **pubkey,
+                            account: (*account).clone(),
+                            slot: *slot,
+                        }
+                    }
+                    (
+                        SubscriptionParams::Signature {
+                            signature,
+                            commitment: CommitmentLevel::Processed,
+                        },
+                        BankEvent::TransactionCommitted {
+                            signature: committed_signature,
+                            err,
+                            slot,
+                            ..
+                        },
+                    ) if signature == *committed_signature => {
+                        finished.push(*id);
+                        Notification::Signature {
+                            err: (*err).clone(),
+                            slot: *slot,
+                        }
+                    }
+                    (
+                        SubscriptionParams::Signature { signature, .. },
+                        BankEvent::TransactionCommitted {
+                            signature: committed_signature,
+                            err,
+                            slot,
+                            ..
+                        },
+                    ) if signature == *committed_signature => {
+                        committed.push((*id, *slot, (*err).clone()));
+                        continue;
+                    }
+                    (
+                        SubscriptionParams::Signature { commitment, .. },
+                        BankEvent::NewSlot {
+                            confirmed,
+                            finalized,
+                            ..
+                        },
+                    ) => {
+                        let commitment_slot = match commitment {
+                            CommitmentLevel::Processed => continue,
+                            CommitmentLevel::Confirmed => confirmed,
+                            CommitmentLevel::Finalized => finalized,
+                        };
+                        match (&subscription.committed_tx, commitment_slot) {
+                            (Some((slot, err)), Some(commitment_slot))
+                                if slot <= commitment_slot =>
+                            {
+                                finished.push(*id);
+                                Notification::Signature {
+                                    err: err.clone(),
+                                    slot: *slot,
+                                }
+                            }
+                            _ => continue,
+                        }
+                    }
+                    (
+                        SubscriptionParams::Logs(filter),
+                        BankEvent::TransactionCommitted {
+                            signature,
+                            message,
+                            err,
+                            logs,
+                            slot,
+                        },
+                    ) if match filter {
+                        LogsFilter::All => true,
+                        LogsFilter::Mentions(pubkey) => {
+                            message.account_keys().iter().any(|key| key == pubkey)
+                        }
+                    } =>
+                    {
+                        Notification::Logs {
+                            signature: **signature,
+                            err: (*err).clone(),
+                            logs: logs.to_vec(),
+                            slot: *slot,
+                        }
+                    }
+                    (
+                        SubscriptionParams::Slot,
+                        BankEvent::NewSlot {
+                            slot, parent, root, ..
+                        },
+                    ) => Notification::Slot {
+                        slot: *slot,
+                        parent: *parent,
+                        root: *root,
+                    },
+                    _ => continue,
+                };
+
+                (subscription.sink)(*id, notification);
+            }
+        }
+
+        if !finished.is_empty() || !committed.is_empty() {
+            let mut subscriptions = self.subscriptions.write().unwrap();
+            for id in finished {
+                subscriptions.remove(&id);
+            }
+            for (id, slot, err) in committed {
+                if let Some(subscription) = subscriptions.get_mut(&id) {
+                    subscription.committed_tx = Some((slot, err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex, RwLock};
+
+    use solana_sdk::{
+        signature::{Keypair, Signer},
+        system_transaction,
+        transaction::SanitizedTransaction,
+    };
+
+    use crate::{
+        rpc::PgRpc,
+        runtime::{bank::PgBank, block_production::BlockProduction},
+    };
+
+    use super::*;
+
+    #[test]
+    fn signature_notified_at_commitment() {
+        let mut bank = PgBank::new(None).unwrap();
+        bank.set_block_production(BlockProduction::Manual);
+        let payer = Keypair::new();
+        bank.request_airdrop(&payer.pubkey(), 1_000_000).unwrap();
+        let tx =
+            system_transaction::transfer(&payer, &payer.pubkey(), 1, bank.get_latest_blockhash());
+        let signature = tx.signatures[0];
+        let bank = Arc::new(RwLock::new(bank));
+        let rpc = PgRpc::new(Arc::clone(&bank));
+
+        let notified = Arc::new(Mutex::new(vec![]));
+        for commitment in [
+            CommitmentLevel::Processed,
+            CommitmentLevel::Confirmed,
+            CommitmentLevel::Finalized,
+        ] {
+            let notified = Arc::clone(&notified);
+            rpc.subscribe(
+                SubscriptionParams::Signature {
+                    signature,
+                    commitment,
+                },
+                Box::new(move |_, _| notified.lock().unwrap().push(commitment)),
+            );
+        }
+
+        bank.write()
+            .unwrap()
+            .process_tx(SanitizedTransaction::from_transaction_for_tests(tx))
+            .unwrap();
+        assert_eq!(*notified.lock().unwrap(), [CommitmentLevel::Processed]);
+
+        bank.write().unwrap().advance_slots(1);
+        assert_eq!(
+            *notified.lock().unwrap(),
+            [CommitmentLevel::Processed, CommitmentLevel::Confirmed]
+        );
+
+        // Subscribing to a committed transaction waits for the commitment too
+        let late = Arc::new(Mutex::new(false));
+        let late_notified = Arc::clone(&late);
+        rpc.subscribe(
+            SubscriptionParams::Signature {
+                signature,
+                commitment: CommitmentLevel::Finalized,
+            },
+            Box::new(move |_, _| *late_notified.lock().unwrap() = true),
+        );
+        assert!(!*late.lock().unwrap());
+
+        bank.write().unwrap().advance_slots(31);
+        assert_eq!(notified.lock().unwrap().len(), 3);
+        assert!(*late.lock().unwrap());
+    }
+}
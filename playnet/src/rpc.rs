@@ -1,42 +1,57 @@
 // Since there is no networking access from WASM, all JSON-RPC methods need to be
 // implemented from scratch to interact with the Playnet runtime.
+//
+// `PgRpc` only uses plain Rust types so that native hosts can use it directly.
+// The WASM bindings wrap it in `wasm::rpc`.
 
-use std::{
-    rc::Rc,
-    str::FromStr,
-    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, Arc},
-};
+use std::sync::{Arc, RwLockReadGuard, RwLockWriteGuard};
 
 use solana_sdk::{
-    feature_set::FeatureSet,
-    instruction::InstructionError,
-    message::{Message, SanitizedMessage},
+    account::Account,
+    clock::{UnixTimestamp, MAX_PROCESSING_AGE},
+    epoch_info::EpochInfo,
+    epoch_schedule::EpochSchedule,
+    hash::Hash,
+    message::{
+        SanitizeMessageError, SanitizedMessage, SanitizedVersionedMessage, VersionedMessage,
+    },
     pubkey::Pubkey,
     signature::Signature,
     slot_history::Slot,
-    transaction::{self, SanitizedTransaction, TransactionError},
+    transaction::{SanitizedTransaction, VersionedTransaction},
 };
-use wasm_bindgen::prelude::*;
 
 use crate::{
-    runtime::bank::PgBank,
+    account_decoder::{AccountDecoder, ParsedAccount},
+    error::{PlaynetError, Result},
+    pubsub::{Notification, NotificationSink, PubSub, SubscriptionId, SubscriptionParams},
+    runtime::{
+        bank::{PgBank, SharedBank},
+        block_production::BlockProduction,
+        bpf_tracer::BpfTracing,
+        commitment::{CommitmentConfig, STATUS_CACHE_SLOTS},
+        faucet::AirdropLimits,
+        transaction_history::TransactionData,
+    },
     types::{
-        GetLatestBlockhashResult, GetSignatureStatusesResult, GetTransactionResult,
-        SendTransactionResult, SimulateTransactionResult, TransactionStatus, WasmAccount,
-        WasmCommitmentLevel,
+        AccountFilter, CommitmentLevel, ConfirmedBlock, InflationRate, LatestBlockhash, LogLimit,
+        SignatureInfo, SimulateTransactionConfig, SimulateTransactionResult, StateOverrides,
+        Supply, TransactionStatus,
     },
-    utils::get_sanitized_tx,
+    utils::sanitize_tx,
 };
 
-#[wasm_bindgen]
 #[derive(Clone)]
 pub struct PgRpc {
-    bank: Rc<RwLock<PgBank>>,
+    bank: SharedBank,
+    pubsub: Arc<PubSub>,
 }
 
 impl PgRpc {
-    pub fn new(bank: Rc<RwLock<PgBank>>) -> Self {
-        Self { bank }
+    pub fn new(bank: SharedBank) -> Self {
+        let pubsub = Arc::new(PubSub::default());
+        bank.write().unwrap().add_observer(pubsub.clone());
+        Self { bank, pubsub }
     }
 
     fn get_bank(&self) -> RwLockReadGuard<'_, PgBank> {
@@ -46,149 +61,520 @@ impl PgRpc {
     fn get_bank_mut(&self) -> RwLockWriteGuard<'_, PgBank> {
         self.bank.write().unwrap()
     }
-}
 
-#[wasm_bindgen]
-impl PgRpc {
-    #[wasm_bindgen(js_name = getAccountInfo)]
-    pub fn get_account_info(&self, pubkey_str: &str) -> WasmAccount {
-        WasmAccount::from(
-            self.get_bank()
-                .get_account_default(&Pubkey::from_str(pubkey_str).unwrap()),
-        )
+    /// Returns `None` if the account doesn't exist
+    pub fn get_account_info(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.get_account_info_with_commitment(pubkey, CommitmentLevel::Processed)
+    }
+
+    /// Returns the account as of the highest slot that reached the commitment
+    /// level, `None` if the account doesn't exist
+    pub fn get_account_info_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentLevel,
+    ) -> Option<Account> {
+        {
+            let bank = self.get_bank();
+            if !bank.has_account_source() {
+                return bank
+                    .get_account_with_commitment(pubkey, commitment)
+                    .cloned();
+            }
+        }
+
+        let mut bank = self.get_bank_mut();
+        bank.fetch_account(pubkey);
+        bank.get_account_with_commitment(pubkey, commitment)
+            .cloned()
+    }
+
+    /// Returns the accounts in the same order as the given keys
+    pub fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Vec<Option<Account>> {
+        self.get_multiple_accounts_with_commitment(pubkeys, CommitmentLevel::Processed)
+    }
+
+    /// Returns the accounts as of the highest slot that reached the commitment
+    /// level, in the same order as the given keys
+    pub fn get_multiple_accounts_with_commitment(
+        &self,
+        pubkeys: &[Pubkey],
+        commitment: CommitmentLevel,
+    ) -> Vec<Option<Account>> {
+        {
+            let bank = self.get_bank();
+            if !bank.has_account_source() {
+                return pubkeys
+                    .iter()
+                    .map(|pubkey| {
+                        bank.get_account_with_commitment(pubkey, commitment)
+                            .cloned()
+                    })
+                    .collect();
+            }
+        }
+
+        let mut bank = self.get_bank_mut();
+        pubkeys
+            .iter()
+            .map(|pubkey| {
+                bank.fetch_account(pubkey);
+                bank.get_account_with_commitment(pubkey, commitment)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// Returns the `jsonParsed` representation of the account data, `None` if
+    /// there is no decoder for the account
+    pub fn decode_account(&self, pubkey: &Pubkey, account: &Account) -> Option<ParsedAccount> {
+        self.get_bank().decode_account(pubkey, account)
+    }
+
+    /// Registers a decoder for the accounts owned by `owner`
+    pub fn add_account_decoder(&self, owner: Pubkey, decoder: Box<dyn AccountDecoder>) {
+        self.get_bank_mut().add_account_decoder(owner, decoder);
+    }
+
+    /// Returns 0 if the account doesn't exist
+    pub fn get_balance(&self, pubkey: &Pubkey) -> u64 {
+        self.get_balance_with_commitment(pubkey, CommitmentLevel::Processed)
+    }
+
+    pub fn get_balance_with_commitment(&self, pubkey: &Pubkey, commitment: CommitmentLevel) -> u64 {
+        self.get_account_info_with_commitment(pubkey, commitment)
+            .map(|account| account.lamports)
+            .unwrap_or_default()
+    }
+
+    /// Returns the accounts owned by the program that match all the filters.
+    ///
+    /// Only the accounts in the bank are included, the account source is not
+    /// consulted.
+    pub fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[AccountFilter],
+    ) -> Vec<(Pubkey, Account)> {
+        self.get_bank()
+            .get_program_accounts(program_id, filters)
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey, account.clone()))
+            .collect()
     }
 
-    #[wasm_bindgen(js_name = getSlot)]
     pub fn get_slot(&self) -> Slot {
         self.get_bank().get_slot()
     }
 
-    #[wasm_bindgen(js_name = getBlockHeight)]
     pub fn get_block_height(&self) -> u64 {
         self.get_bank().get_block_height()
     }
 
-    #[wasm_bindgen(js_name = getGenesisHash)]
-    pub fn get_genesis_hash(&self) -> String {
-        self.get_bank().get_genesis_hash().to_string()
+    /// Returns the highest slot that reached the commitment level, the first
+    /// slot if no slot has reached it yet
+    pub fn get_slot_with_commitment(&self, commitment: CommitmentLevel) -> Slot {
+        self.get_bank()
+            .get_commitment_slot(commitment)
+            .unwrap_or_default()
     }
 
-    #[wasm_bindgen(js_name = getLatestBlockhash)]
-    pub fn get_latest_blockhash(&self) -> GetLatestBlockhashResult {
+    /// Returns the block height of the highest slot that reached the
+    /// commitment level
+    pub fn get_block_height_with_commitment(&self, commitment: CommitmentLevel) -> u64 {
         let bank = self.get_bank();
-        GetLatestBlockhashResult::new(bank.get_latest_blockhash(), bank.get_block_height())
+        // Every slot has a block
+        match bank.get_commitment_slot(commitment) {
+            Some(slot) => bank
+                .get_block_height()
+                .saturating_sub(bank.get_slot() - slot),
+            None => 0,
+        }
+    }
+
+    pub fn get_commitment_config(&self) -> CommitmentConfig {
+        self.get_bank().get_commitment_config()
+    }
+
+    pub fn set_commitment_config(&self, config: CommitmentConfig) {
+        self.get_bank_mut().set_commitment_config(config);
+    }
+
+    pub fn get_block_production(&self) -> BlockProduction {
+        self.get_bank().get_block_production()
+    }
+
+    pub fn set_block_production(&self, block_production: BlockProduction) {
+        self.get_bank_mut().set_block_production(block_production);
+    }
+
+    /// Ends the given number of slots and returns the new slot
+    pub fn advance_slots(&self, count: u64) -> Slot {
+        let mut bank = self.get_bank_mut();
+        bank.advance_slots(count);
+        bank.get_slot()
+    }
+
+    pub fn get_log_limit(&self) -> LogLimit {
+        self.get_bank().get_log_limit()
+    }
+
+    pub fn set_log_limit(&self, log_limit: LogLimit) {
+        self.get_bank_mut().set_log_limit(log_limit);
+    }
+
+    pub fn get_bpf_tracing(&self) -> BpfTracing {
+        self.get_bank().get_bpf_tracing().clone()
+    }
+
+    pub fn set_bpf_tracing(&self, bpf_tracing: BpfTracing) {
+        self.get_bank_mut().set_bpf_tracing(bpf_tracing);
+    }
+
+    pub fn get_genesis_hash(&self) -> Hash {
+        self.get_bank().get_genesis_hash()
+    }
+
+    pub fn get_latest_blockhash(&self) -> LatestBlockhash {
+        let bank = self.get_bank();
+        LatestBlockhash {
+            blockhash: bank.get_latest_blockhash(),
+            last_valid_block_height: bank.get_block_height() + MAX_PROCESSING_AGE as u64,
+        }
+    }
+
+    pub fn get_epoch_info(&self) -> EpochInfo {
+        self.get_bank().get_epoch_info()
+    }
+
+    pub fn get_epoch_schedule(&self) -> EpochSchedule {
+        *self.get_bank().get_epoch_schedule()
+    }
+
+    pub fn get_supply(&self) -> Supply {
+        let total = self.get_bank().get_capitalization();
+        Supply {
+            total,
+            circulating: total,
+            non_circulating: 0,
+            non_circulating_accounts: vec![],
+        }
+    }
+
+    pub fn get_inflation_rate(&self) -> InflationRate {
+        let bank = self.get_bank();
+        let inflation = bank.get_inflation();
+        let year = bank.get_slot_in_year_for_inflation();
+        InflationRate {
+            total: inflation.total(year),
+            validator: inflation.validator(year),
+            foundation: inflation.foundation(year),
+            epoch: bank.get_epoch_info().epoch,
+        }
+    }
+
+    pub fn get_identity(&self) -> Pubkey {
+        self.get_bank().get_identity()
     }
 
-    #[wasm_bindgen(js_name = getMinimumBalanceForRentExemption)]
     pub fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> u64 {
         self.get_bank()
             .get_minimum_balance_for_rent_exemption(data_len)
     }
 
-    #[wasm_bindgen(js_name = getFeeForMessage)]
-    pub fn get_fee_for_message(&self, serialized_msg: &[u8]) -> Option<u64> {
-        let msg: Message = serde_json::from_slice(serialized_msg).unwrap();
-        self.get_bank()
-            .get_fee_for_message(&SanitizedMessage::try_from(msg).unwrap())
+    pub fn get_fee_for_message(&self, msg: VersionedMessage) -> Result<Option<u64>> {
+        let bank = self.get_bank();
+        let msg = SanitizedMessage::try_new(
+            SanitizedVersionedMessage::try_from(msg).map_err(SanitizeMessageError::from)?,
+            bank.address_loader(),
+        )?;
+        Ok(bank.get_fee_for_message(&msg))
     }
 
-    #[wasm_bindgen(js_name = simulateTransaction)]
-    pub fn simulate_transaction(&self, serialized_tx: &[u8]) -> SimulateTransactionResult {
-        let sanitized_transaction = match get_sanitized_tx(serialized_tx) {
-            Ok(tx) => tx,
-            Err(err) => return SimulateTransactionResult::new_error(err),
-        };
+    /// Sanitizes the transaction, loading the addresses of the v0 messages from
+    /// the lookup tables in the bank
+    pub fn sanitize_transaction(&self, tx: VersionedTransaction) -> Result<SanitizedTransaction> {
+        if let VersionedMessage::V0(message) = &tx.message {
+            // Only block the other readers if the lookup tables need to be fetched
+            if self.get_bank().has_account_source() {
+                let mut bank = self.get_bank_mut();
+                for lookup in &message.address_table_lookups {
+                    bank.fetch_account(&lookup.account_key);
+                }
+            }
+        }
+
+        sanitize_tx(tx, self.get_bank().address_loader())
+    }
+
+    pub fn simulate_transaction(&self, tx: &SanitizedTransaction) -> SimulateTransactionResult {
+        self.simulate_transaction_with_overrides(tx, &StateOverrides::default(), None)
+    }
+
+    /// Simulates the transaction against the overridden account states, with
+    /// the log limit of the bank unless `log_limit` is given
+    pub fn simulate_transaction_with_overrides(
+        &self,
+        tx: &SanitizedTransaction,
+        overrides: &StateOverrides,
+        log_limit: Option<LogLimit>,
+    ) -> SimulateTransactionResult {
+        // Only block the other readers if the accounts need to be fetched
+        if self.get_bank().has_account_source() {
+            self.get_bank_mut().fetch_tx_accounts(tx);
+        }
 
         let bank = self.get_bank();
-        bank.simulate_tx(&sanitized_transaction)
+        let log_limit = log_limit.unwrap_or_else(|| bank.get_log_limit());
+        bank.simulate_tx_with_overrides(tx, overrides, log_limit)
     }
 
-    #[wasm_bindgen(js_name = sendTransaction)]
-    pub fn send_transaction(&self, serialized_tx: &[u8]) -> SendTransactionResult {
-        let sanitized_tx = match get_sanitized_tx(serialized_tx) {
-            Ok(sanitized_tx) => sanitized_tx,
-            Err(err) => return SendTransactionResult::new_error(err),
+    /// Sanitizes and simulates the transaction with the given options
+    pub fn simulate_transaction_with_config(
+        &self,
+        mut tx: VersionedTransaction,
+        config: &SimulateTransactionConfig,
+    ) -> Result<SimulateTransactionResult> {
+        if let Some(min_context_slot) = config.min_context_slot {
+            let context_slot = self.get_slot();
+            if context_slot < min_context_slot {
+                return Err(PlaynetError::MinContextSlotNotReached { context_slot });
+            }
+        }
+
+        let replacement_blockhash = if config.replace_recent_blockhash {
+            if config.sig_verify {
+                return Err(PlaynetError::InvalidParam(
+                    "sigVerify may not be used with replaceRecentBlockhash".into(),
+                ));
+            }
+
+            let latest_blockhash = self.get_latest_blockhash();
+            tx.message.set_recent_blockhash(latest_blockhash.blockhash);
+            Some(latest_blockhash)
+        } else {
+            None
         };
 
-        fn verify_transaction(
-            transaction: &SanitizedTransaction,
-            feature_set: &FeatureSet,
-        ) -> transaction::Result<()> {
-            transaction.verify()?;
-            transaction.verify_precompiles(&Arc::new(feature_set.clone()))?;
-            Ok(())
+        let tx = self.sanitize_transaction(tx)?;
+        if config.sig_verify {
+            self.verify_transaction(&tx)?;
         }
-
-        let mut bank = self.get_bank_mut();
-        if let Err(err) = verify_transaction(&sanitized_tx, &bank.feature_set()) {
-            return SendTransactionResult::new_error(err);
+        let account_count = tx.message().account_keys().len();
+        if let Some(addresses) = &config.accounts {
+            if addresses.len() > account_count {
+                return Err(PlaynetError::InvalidParam(format!(
+                    "Too many accounts provided; max {}",
+                    account_count
+                )));
+            }
         }
 
-        match bank.process_tx(sanitized_tx) {
-            Ok(tx_hash) => SendTransactionResult::new(tx_hash.to_string()),
-            Err(err) => SendTransactionResult::new_error(err),
+        let mut result = self.simulate_transaction_with_overrides(
+            &tx,
+            &config.state_overrides,
+            config.log_limit,
+        );
+        result.replacement_blockhash = replacement_blockhash;
+        if !config.inner_instructions {
+            result.inner_instructions = None;
         }
+        result.accounts = config.accounts.as_ref().map(|addresses| {
+            addresses
+                .iter()
+                .map(|address| {
+                    // Failed simulations don't have the account states
+                    result.result.as_ref().ok()?;
+                    result
+                        .post_accounts
+                        .iter()
+                        .find(|(pubkey, _)| pubkey == address)
+                        .map(|(_, account)| account.clone().into())
+                })
+                .collect()
+        });
+
+        Ok(result)
+    }
+
+    /// Verifies the signatures and the precompiles of the transaction
+    pub fn verify_transaction(&self, tx: &SanitizedTransaction) -> Result<()> {
+        tx.verify()?;
+        tx.verify_precompiles(self.get_bank().feature_set())
+            .map_err(PlaynetError::PrecompileVerificationFailure)
+    }
+
+    /// Verifies and processes the transaction, returning its signature
+    pub fn send_transaction(&self, tx: SanitizedTransaction) -> Result<Signature> {
+        // Verify before taking the write lock, verification doesn't need it
+        self.verify_transaction(&tx)?;
+        Ok(self.get_bank_mut().process_tx(tx)?)
     }
 
-    #[wasm_bindgen(js_name = getSignatureStatuses)]
-    pub fn get_signature_statuses(&self, signatures: Vec<JsValue>) -> GetSignatureStatusesResult {
+    /// Returns `None` for the signatures that don't exist.
+    ///
+    /// Only the transactions in the status cache are found unless
+    /// `search_transaction_history` is set.
+    pub fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+        search_transaction_history: bool,
+    ) -> Vec<Option<TransactionStatus>> {
         let bank = self.get_bank();
-        let statuses = signatures
+        let current_slot = bank.get_slot();
+        signatures
             .iter()
-            .map(|js_signature| {
-                let signature = Signature::from_str(&js_signature.as_string().unwrap()).unwrap();
-                bank.get_tx(&signature).and_then(|tx_data| {
-                    let current_slot = bank.get_slot();
-                    let confirmations = current_slot - tx_data.get_slot();
-                    let confirmation_status = if confirmations == 0 {
-                        WasmCommitmentLevel::Processed
-                    } else if confirmations < 32 {
-                        WasmCommitmentLevel::Confirmed
-                    } else {
-                        WasmCommitmentLevel::Finalized
-                    };
-                    let err = tx_data
-                        .get_meta()
-                        .as_ref()
-                        .map(|meta| meta.err.clone())
-                        .unwrap_or(None);
-
-                    Some(TransactionStatus::new(
-                        Some(confirmation_status),
-                        Some(confirmations as usize),
-                        tx_data.get_slot(),
-                        err,
-                    ))
-                })
+            .map(|signature| {
+                let tx_data = bank.get_tx(signature).filter(|tx_data| {
+                    search_transaction_history
+                        || current_slot - tx_data.get_slot() < STATUS_CACHE_SLOTS
+                })?;
+                let err = tx_data
+                    .get_meta()
+                    .as_ref()
+                    .and_then(|meta| meta.err.clone());
+
+                Some(TransactionStatus::new(
+                    Some(bank.get_commitment_level(tx_data.get_slot())),
+                    Some((current_slot - tx_data.get_slot()) as usize),
+                    tx_data.get_slot(),
+                    err,
+                ))
             })
+            .collect()
+    }
+
+    pub fn get_transaction(&self, signature: &Signature) -> Option<TransactionData> {
+        self.get_bank()
+            .get_tx(signature)
+            .map(|data| data.to_owned())
+    }
+
+    /// Returns `None` if the transaction hasn't reached the commitment level
+    pub fn get_transaction_with_commitment(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentLevel,
+    ) -> Option<TransactionData> {
+        let bank = self.get_bank();
+        bank.get_tx(signature)
+            .filter(|tx_data| bank.get_commitment_level(tx_data.get_slot()) >= commitment)
+            .map(|data| data.to_owned())
+    }
+
+    /// Returns `None` if the block doesn't exist
+    pub fn get_block(&self, slot: Slot) -> Option<ConfirmedBlock> {
+        let bank = self.get_bank();
+        let block = bank.get_block(slot)?.clone();
+        let transactions = block
+            .signatures
+            .iter()
+            .filter_map(|signature| bank.get_tx(signature).cloned())
             .collect();
 
-        GetSignatureStatusesResult::new(statuses)
+        Some(ConfirmedBlock {
+            block,
+            transactions,
+        })
     }
 
-    #[wasm_bindgen(js_name = getTransaction)]
-    pub fn get_transaction(&self, signature_str: &str) -> GetTransactionResult {
-        let signature = Signature::from_str(signature_str).unwrap();
+    /// Returns the slots of the blocks between the given slots, inclusive
+    pub fn get_blocks(&self, start_slot: Slot, end_slot: Option<Slot>) -> Vec<Slot> {
         let bank = self.get_bank();
-        GetTransactionResult::new(bank.get_tx(&signature).map(|data| data.to_owned()))
+        bank.get_blocks(start_slot, end_slot.unwrap_or_else(|| bank.get_slot()))
     }
 
-    /// TODO: Create a transaction to airdrop. Currently we set the account lamports directly.
-    #[wasm_bindgen(js_name = requestAirdrop)]
-    pub fn request_airdrop(&self, pubkey_str: &str, lamports: u64) -> SendTransactionResult {
-        let pubkey = Pubkey::from_str(pubkey_str).unwrap();
-        let mut bank = self.get_bank_mut();
-        let mut account = bank.get_account_default(&pubkey);
-        match account.lamports.checked_add(lamports) {
-            Some(res) => {
-                account.lamports = res;
-                bank.set_account(pubkey, account);
-                SendTransactionResult::new(Signature::new_unique().to_string())
+    pub fn get_blocks_with_limit(&self, start_slot: Slot, limit: usize) -> Vec<Slot> {
+        self.get_bank().get_blocks_with_limit(start_slot, limit)
+    }
+
+    /// Returns `None` if the block doesn't exist
+    pub fn get_block_time(&self, slot: Slot) -> Option<UnixTimestamp> {
+        self.get_bank()
+            .get_block(slot)
+            .and_then(|block| block.block_time)
+    }
+
+    pub fn get_first_available_block(&self) -> Slot {
+        self.get_bank().get_first_available_block()
+    }
+
+    /// Returns the transactions that reference the address and reached the
+    /// commitment level, newest first
+    pub fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<&Signature>,
+        until: Option<&Signature>,
+        limit: usize,
+        commitment: CommitmentLevel,
+    ) -> Vec<SignatureInfo> {
+        let bank = self.get_bank();
+        bank.get_signatures_for_address(address, before, until, limit, commitment)
+            .into_iter()
+            .map(|(signature, tx_data)| SignatureInfo {
+                signature: *signature,
+                slot: tx_data.get_slot(),
+                confirmation_status: Some(bank.get_commitment_level(tx_data.get_slot())),
+                err: tx_data
+                    .get_meta()
+                    .as_ref()
+                    .and_then(|meta| meta.err.clone()),
+                memo: tx_data.get_memo(),
+                block_time: tx_data.get_block_time(),
+            })
+            .collect()
+    }
+
+    /// Airdrops from the faucet with a system transfer that gets recorded in
+    /// the transaction history
+    pub fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature> {
+        self.get_bank_mut().request_airdrop(pubkey, lamports)
+    }
+
+    pub fn get_faucet_pubkey(&self) -> Pubkey {
+        self.get_bank().get_faucet_pubkey()
+    }
+
+    pub fn set_airdrop_limits(&self, limits: AirdropLimits) {
+        self.get_bank_mut().set_airdrop_limits(limits);
+    }
+
+    /// Subscribes to the bank state changes.
+    ///
+    /// The sink is called while the bank is locked and must not access the bank.
+    pub fn subscribe(&self, params: SubscriptionParams, sink: NotificationSink) -> SubscriptionId {
+        // Hold the lock so that the transaction can't get committed in between
+        let bank = self.get_bank();
+
+        // Already committed transactions get notified immediately if they
+        // reached the commitment level, otherwise once they reach it
+        if let SubscriptionParams::Signature {
+            signature,
+            commitment,
+        } = &params
+        {
+            if let Some(tx_data) = bank.get_tx(signature) {
+                let err = tx_data
+                    .get_meta()
+                    .as_ref()
+                    .and_then(|meta| meta.err.clone());
+                let slot = tx_data.get_slot();
+                if bank.get_commitment_level(slot) < *commitment {
+                    return self.pubsub.subscribe_committed_tx(params, sink, slot, err);
+                }
+
+                let id = self.pubsub.next_id();
+                sink(id, Notification::Signature { err, slot });
+                return id;
             }
-            None => SendTransactionResult::new_error(TransactionError::InstructionError(
-                0,
-                InstructionError::ArithmeticOverflow,
-            )),
         }
+
+        self.pubsub.subscribe(params, sink)
+    }
+
+    /// Returns `false` if the subscription doesn't exist
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.pubsub.unsubscribe(id)
     }
 }
@@ -0,0 +1,96 @@
+// Account dumps in the format of `solana account <pubkey> --output json`, which
+// is also the format `solana-test-validator --account` accepts.
+
+use std::{fs, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::error::{PlaynetError, Result};
+
+/// Keyed account as it appears in an account dump
+#[derive(Serialize, Deserialize)]
+pub(crate) struct DumpedAccount {
+    pub(crate) pubkey: String,
+    pub(crate) account: UiAccount,
+}
+
+/// Account with `base64` encoded data, as returned by JSON-RPC
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UiAccount {
+    pub(crate) lamports: u64,
+    pub(crate) data: (String, String),
+    pub(crate) owner: String,
+    pub(crate) executable: bool,
+    pub(crate) rent_epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) space: Option<u64>,
+}
+
+impl UiAccount {
+    pub(crate) fn from_account(account: &Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            data: (base64::encode(&account.data), "base64".into()),
+            owner: account.owner.to_string(),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            space: Some(account.data.len() as u64),
+        }
+    }
+
+    pub(crate) fn into_account(self) -> Option<Account> {
+        let (data, encoding) = self.data;
+        if encoding != "base64" {
+            return None;
+        }
+
+        Some(Account {
+            lamports: self.lamports,
+            data: base64::decode(data).ok()?,
+            owner: Pubkey::from_str(&self.owner).ok()?,
+            executable: self.executable,
+            rent_epoch: self.rent_epoch,
+        })
+    }
+}
+
+/// Reads a single account dump
+pub fn read_account_file<P: AsRef<Path>>(path: P) -> Result<(Pubkey, Account)> {
+    let dump = fs::read_to_string(&path)?;
+    let dumped_account = serde_json::from_str::<DumpedAccount>(&dump)?;
+    let invalid_data = || PlaynetError::InvalidAccountDump(path.as_ref().display().to_string());
+
+    let pubkey = Pubkey::from_str(&dumped_account.pubkey).map_err(|_| invalid_data())?;
+    let account = dumped_account
+        .account
+        .into_account()
+        .ok_or_else(invalid_data)?;
+    Ok((pubkey, account))
+}
+
+/// Reads every `.json` account dump in the directory
+pub fn read_account_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<(Pubkey, Account)>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.retain(|path| path.extension().map_or(false, |ext| ext == "json"));
+    paths.sort();
+
+    paths.iter().map(read_account_file).collect()
+}
+
+/// Writes the account dump to the given path
+pub fn write_account_file<P: AsRef<Path>>(
+    path: P,
+    pubkey: &Pubkey,
+    account: &Account,
+) -> Result<()> {
+    let dumped_account = DumpedAccount {
+        pubkey: pubkey.to_string(),
+        account: UiAccount::from_account(account),
+    };
+    fs::write(path, serde_json::to_string_pretty(&dumped_account)?)?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+// Account sources are consulted when the bank doesn't have an account. This
+// allows executing real-world transactions without copying the whole state of
+// a cluster into the bank: only the accounts a transaction references get
+// materialized.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde_json::{json, Value};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use super::account_dump::{read_account_file, UiAccount};
+
+/// Fallback provider of accounts that don't exist in the bank.
+///
+/// Sources must be `Send + Sync` because the bank is shared between threads.
+pub trait AccountSource: Send + Sync {
+    /// Returns `None` if the account doesn't exist in the source either
+    fn get_account(&self, pubkey: &Pubkey) -> Option<Account>;
+}
+
+/// Reads accounts from a directory of JSON account dumps.
+///
+/// Each account is expected to be in `<dir>/<pubkey>.json` with the format of
+/// `solana account <pubkey> --output json`.
+pub struct DirectoryAccountSource {
+    dir: PathBuf,
+}
+
+impl DirectoryAccountSource {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl AccountSource for DirectoryAccountSource {
+    fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        let path = self.dir.join(format!("{}.json", pubkey));
+        read_account_file(path).ok().map(|(_, account)| account)
+    }
+}
+
+/// Transport used by `RpcAccountSource` to send JSON-RPC requests
+pub trait RpcTransport: Send + Sync {
+    /// Sends the JSON-RPC request and returns the response, `None` on failure
+    fn send(&self, request: Value) -> Option<Value>;
+}
+
+/// Fetches accounts from a JSON-RPC node with `getAccountInfo`
+pub struct RpcAccountSource<T: RpcTransport> {
+    transport: T,
+}
+
+impl<T: RpcTransport> RpcAccountSource<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+}
+
+impl<T: RpcTransport> AccountSource for RpcAccountSource<T> {
+    fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        let response = self.transport.send(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [pubkey.to_string(), { "encoding": "base64" }],
+        }))?;
+        let value = response.get("result")?.get("value")?.to_owned();
+        serde_json::from_value::<Option<UiAccount>>(value)
+            .ok()
+            .flatten()
+            .and_then(|account| account.into_account())
+    }
+}
+
+/// In-process mock of a JSON-RPC node that only answers `getAccountInfo`
+#[derive(Default)]
+pub struct MockRpcNode {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl MockRpcNode {
+    pub fn new(accounts: HashMap<Pubkey, Account>) -> Self {
+        Self { accounts }
+    }
+
+    pub fn add_account(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+}
+
+impl RpcTransport for MockRpcNode {
+    fn send(&self, request: Value) -> Option<Value> {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        if request.get("method").and_then(Value::as_str) != Some("getAccountInfo") {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": "Method not found" },
+            }));
+        }
+
+        let pubkey = request
+            .get("params")
+            .and_then(|params| params.get(0))
+            .and_then(Value::as_str)
+            .and_then(|pubkey_str| Pubkey::from_str(pubkey_str).ok());
+        let pubkey = match pubkey {
+            Some(pubkey) => pubkey,
+            None => {
+                return Some(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32602, "message": "Invalid param: Invalid" },
+                }))
+            }
+        };
+
+        let value = self.accounts.get(&pubkey).map(UiAccount::from_account);
+
+        Some(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": { "context": { "slot": 0 }, "value": value },
+        }))
+    }
+}
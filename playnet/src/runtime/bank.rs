@@ -1,12 +1,15 @@
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, mem,
     num::NonZeroUsize,
+    path::Path,
     rc::Rc,
     sync::{Arc, RwLock},
 };
 
 use serde::{Deserialize, Serialize};
+use solana_address_lookup_table_program::{error::AddressLookupError, state::AddressLookupTable};
 use solana_bpf_loader_program::process_instruction as process_bpf_loader_instruction;
 use solana_program_runtime::{
     compute_budget::ComputeBudget, executor_cache::Executors,
@@ -18,39 +21,69 @@ use solana_sdk::{
     account_utils::StateMut,
     bpf_loader,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
-    clock::Clock,
+    clock::{Clock, UnixTimestamp, DEFAULT_TICKS_PER_SLOT},
+    epoch_info::EpochInfo,
+    epoch_schedule::EpochSchedule,
     feature_set::{self, FeatureSet},
     fee::FeeStructure,
-    hash::Hash,
+    fee_calculator::FeeRateGovernor,
+    genesis_config::{GenesisConfig, DEFAULT_GENESIS_FILE},
+    hash::{hashv, Hash},
+    inflation::Inflation,
     instruction::CompiledInstruction,
     message::{
         v0::{LoadedAddresses, MessageAddressTableLookup},
         AddressLoaderError, SanitizedMessage,
     },
+    nonce,
+    nonce_account::verify_nonce_account,
     ed25519_program,
     secp256k1_program,
     native_loader,
+    poh_config::PohConfig,
     pubkey::Pubkey,
     rent::Rent,
-    signature::Signature,
+    signature::{Keypair, Signature},
     slot_history::Slot,
     system_program,
+    timing::{slot_duration_from_slots_per_year, years_as_slots},
     sysvar::{self, instructions::{construct_instructions_data}, Sysvar},
     transaction::{self, AddressLoader, SanitizedTransaction, TransactionError},
     transaction_context::{
         ExecutionRecord, TransactionAccount, TransactionContext,
-        TransactionReturnData,
+        InstructionTrace, TransactionReturnData,
     },
 };
-
-use crate::{serde::bank_accounts, types::SimulateTransactionResult, utils::create_blockhash};
+use tracing::{debug, trace, warn};
+
+use crate::{
+    account_decoder::{AccountDecoder, AccountDecoders, ParsedAccount},
+    error,
+    serde::bank_accounts,
+    types::{AccountFilter, CommitmentLevel, LogLimit, SimulateTransactionResult, StateOverrides},
+    utils::create_blockhash,
+};
 
 use super::{
+    account_dump::{read_account_dir, read_account_file, write_account_file},
+    account_source::AccountSource,
+    block_production::BlockProduction,
+    block_store::Block,
+    blockhash_queue::BlockhashQueue,
+    bpf_tracer::{write_traces, BpfTracing},
+    commitment::{CommitmentConfig, CommitmentTracker},
+    events::{BankEvent, BankObserver},
+    faucet::{AirdropLimits, Faucet, FAUCET_LAMPORTS},
+    invocation::{finish_recording, record_invocation, start_recording, Invocation},
+    memo_program::{self, process_memo_instruction},
     message_processor::MessageProcessor,
     system_instruction_processor::{
         get_system_account_kind, process_system_instruction, SystemAccountKind,
     },
-    transaction_history::{ConfirmedTransactionMeta, TransactionData},
+    transaction_history::{
+        CompiledInnerInstruction, ConfirmedTransactionMeta, InstructionComputeUnits,
+        ProgramExecutionDetails, TransactionData,
+    },
 };
 
 #[derive(Serialize, Deserialize)]
@@ -59,6 +92,15 @@ pub struct PgBank {
     #[serde(with = "bank_accounts")]
     accounts: BankAccounts,
 
+    /// Keys of the accounts with lamports by their owner, rebuilt from
+    /// `accounts` on init
+    #[serde(skip)]
+    owner_index: HashMap<Pubkey, HashSet<Pubkey>>,
+
+    /// Total lamports of all accounts, rebuilt from `accounts` on init
+    #[serde(skip)]
+    capitalization: u64,
+
     /// Where all the transactions are stored.
     ///
     /// Currently transactions are only
@@ -69,18 +111,68 @@ pub struct PgBank {
     #[serde(skip)]
     txs: BankTxs,
 
+    /// Signatures of the stored transactions by the accounts they reference,
+    /// oldest first
+    #[serde(skip)]
+    address_signatures: HashMap<Pubkey, Vec<Signature>>,
+
+    /// Produced blocks by their slot. Not persisted since the transactions
+    /// they consist of are not persisted either.
+    #[serde(skip)]
+    blocks: BankBlocks,
+
+    /// Signatures of the transactions processed in the current slot
+    #[serde(skip)]
+    slot_signatures: Vec<Signature>,
+
     /// Bank's slot (i.e. block)
     slot: Slot,
 
     /// Bank's block height
     block_height: u64,
 
+    /// Number of committed transactions since genesis
+    #[serde(default)]
+    transaction_count: u64,
+
     /// Bank's first hash
     genesis_hash: Hash,
 
     /// Bank's latest blockhash
     latest_blockhash: Hash,
 
+    /// When the bank was created relative to the UNIX epoch
+    #[serde(default)]
+    creation_time: UnixTimestamp,
+
+    /// Rent configuration
+    #[serde(default)]
+    rent: Rent,
+
+    /// How slots map to epochs
+    #[serde(default)]
+    epoch_schedule: EpochSchedule,
+
+    /// Transaction fee configuration
+    #[serde(default = "PgBank::default_fee_rate_governor")]
+    fee_rate_governor: FeeRateGovernor,
+
+    /// Inflation configuration, only reported since Playnet doesn't pay rewards
+    #[serde(default)]
+    inflation: Inflation,
+
+    /// Number of slots in a year, depends on the slot duration
+    #[serde(default = "PgBank::default_slots_per_year")]
+    slots_per_year: f64,
+
+    /// Source of the airdrops, the keypair is persisted but the limits are not
+    #[serde(default)]
+    faucet: Faucet,
+
+    /// Recent blockhashes, the latest one is `latest_blockhash`
+    #[serde(default)]
+    blockhash_queue: BlockhashQueue,
+
     /// Essential programs that don't get deployed with transactions
     #[serde(skip)]
     builtin_programs: Vec<BuiltinProgram>,
@@ -91,107 +183,391 @@ pub struct PgBank {
 
     /// Active/inactive features(not able to change yet)
     #[serde(skip)]
-    feature_set: Rc<FeatureSet>,
+    feature_set: Arc<FeatureSet>,
+
+    /// Fallback provider consulted for accounts that don't exist in the bank
+    #[serde(skip)]
+    account_source: Option<Box<dyn AccountSource>>,
+
+    /// Hooks that get notified of the committed state changes
+    #[serde(skip)]
+    observers: Vec<Arc<dyn BankObserver>>,
+
+    /// Decoders of the `jsonParsed` account data
+    #[serde(skip)]
+    account_decoders: AccountDecoders,
+
+    /// Simulated confirmation and finalization of the slots
+    #[serde(skip)]
+    commitment: CommitmentTracker,
+
+    /// When the slots end
+    #[serde(skip)]
+    block_production: BlockProduction,
+
+    /// Log limit of the transactions
+    #[serde(skip)]
+    log_limit: LogLimit,
+
+    /// Tracing of the instructions executed by BPF programs
+    #[serde(skip)]
+    bpf_tracing: BpfTracing,
 }
 
-impl PgBank {
-    const LAMPORTS_PER_SIGNATURE: u64 = 0;
+/// Serialized fields of the bank in the version 1 snapshots
+#[derive(Deserialize)]
+pub(super) struct PgBankV1 {
+    accounts: BankAccounts,
+    slot: Slot,
+    block_height: u64,
+    genesis_hash: Hash,
+    latest_blockhash: Hash,
+    creation_time: UnixTimestamp,
+    rent: Rent,
+    epoch_schedule: EpochSchedule,
+    fee_rate_governor: FeeRateGovernor,
+}
+
+impl From<PgBankV1> for PgBank {
+    fn from(v1: PgBankV1) -> Self {
+        let mut bank = Self::empty(v1.accounts, v1.genesis_hash);
+        bank.slot = v1.slot;
+        bank.block_height = v1.block_height;
+        bank.latest_blockhash = v1.latest_blockhash;
+        bank.creation_time = v1.creation_time;
+        bank.rent = v1.rent;
+        bank.epoch_schedule = v1.epoch_schedule;
+        bank.fee_rate_governor = v1.fee_rate_governor;
+        bank
+    }
+}
+
+/// Serialized fields of the bank in the version 2 snapshots
+#[derive(Deserialize)]
+pub(super) struct PgBankV2 {
+    accounts: BankAccounts,
+    slot: Slot,
+    block_height: u64,
+    transaction_count: u64,
+    genesis_hash: Hash,
+    latest_blockhash: Hash,
+    creation_time: UnixTimestamp,
+    rent: Rent,
+    epoch_schedule: EpochSchedule,
+    fee_rate_governor: FeeRateGovernor,
+    inflation: Inflation,
+    slots_per_year: f64,
+}
+
+impl From<PgBankV2> for PgBank {
+    fn from(v2: PgBankV2) -> Self {
+        let mut bank = Self::empty(v2.accounts, v2.genesis_hash);
+        bank.slot = v2.slot;
+        bank.block_height = v2.block_height;
+        bank.transaction_count = v2.transaction_count;
+        bank.latest_blockhash = v2.latest_blockhash;
+        bank.creation_time = v2.creation_time;
+        bank.rent = v2.rent;
+        bank.epoch_schedule = v2.epoch_schedule;
+        bank.fee_rate_governor = v2.fee_rate_governor;
+        bank.inflation = v2.inflation;
+        bank.slots_per_year = v2.slots_per_year;
+        bank
+    }
+}
+
+/// Serialized fields of the bank in the version 3 snapshots. `bincode` is not
+/// self-describing, the fields of `v2` are read as if they were inlined.
+#[derive(Deserialize)]
+pub(super) struct PgBankV3 {
+    v2: PgBankV2,
+    faucet: Faucet,
+}
+
+impl From<PgBankV3> for PgBank {
+    fn from(v3: PgBankV3) -> Self {
+        let mut bank = Self::from(v3.v2);
+        bank.faucet = v3.faucet;
+        bank
+    }
+}
+
+/// Bank handle shared between the Playnet and the RPC. Simulations and reads
+/// only take the read lock so they can run concurrently on native hosts.
+pub type SharedBank = Arc<RwLock<PgBank>>;
 
-    pub fn new(maybe_bank_string: Option<String>) -> Self {
+// Native hosts move the bank between threads
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PgBank>();
+};
+
+impl PgBank {
+    pub fn new(maybe_bank_string: Option<String>) -> error::Result<Self> {
         let bank = match maybe_bank_string {
-            Some(bank_string) => serde_json::from_str::<Self>(&bank_string).unwrap(),
-            None => {
-                let genesis_hash = create_blockhash(b"playnet");
-                Self {
-                    accounts: HashMap::new(),
-                    txs: HashMap::new(),
-                    slot: 0,
-                    block_height: 0,
-                    genesis_hash,
-                    latest_blockhash: genesis_hash,
-                    builtin_programs: vec![],
-                    sysvar_cache: RwLock::new(SysvarCache::default()),
-                    feature_set: Rc::new(FeatureSet::default()),
-                }
-            }
+            Some(bank_string) => serde_json::from_str::<Self>(&bank_string)?,
+            None => Self::empty(HashMap::new(), create_blockhash(b"playnet")),
         };
 
-        bank.init()
+        Ok(bank.init())
     }
 
     pub fn new_with_more(accounts: BankAccounts, genesis_hash: Hash) -> Self {
-        let bank = Self {
+        Self::empty(accounts, genesis_hash).init()
+    }
+
+    /// Creates the bank with the accounts, rent, fee rate governor, epoch
+    /// schedule, native programs and creation time of the genesis config.
+    ///
+    /// Native programs that don't have a builtin implementation in the bank only
+    /// get their accounts created and fail with `UnsupportedProgramId` when invoked.
+    ///
+    /// No lamports are created for the faucet, it airdrops the lamports the
+    /// genesis gives to the account of `faucet_keypair`. Airdrops fail without
+    /// the keypair.
+    pub fn from_genesis_config(
+        genesis_config: &GenesisConfig,
+        faucet_keypair: Option<Keypair>,
+    ) -> Self {
+        let mut accounts = HashMap::new();
+        for (pubkey, account) in genesis_config
+            .accounts
+            .iter()
+            .chain(genesis_config.rewards_pools.iter())
+        {
+            accounts.insert(*pubkey, account.to_owned());
+        }
+        for (name, program_id) in &genesis_config.native_instruction_processors {
+            let account = native_loader::create_loadable_account_with_fields(name, (1, 0));
+            accounts.insert(*program_id, Account::from(account));
+        }
+
+        let faucet = faucet_keypair.map(Faucet::new).unwrap_or_default();
+        // An unfunded faucet still gets its account so that it doesn't get
+        // funded like the faucets of the banks from before the faucet existed
+        accounts.entry(faucet.pubkey()).or_default();
+
+        let mut bank = Self::empty(accounts, genesis_config.hash());
+        bank.faucet = faucet;
+        bank.creation_time = genesis_config.creation_time;
+        bank.rent = genesis_config.rent;
+        bank.epoch_schedule = genesis_config.epoch_schedule;
+        bank.fee_rate_governor = genesis_config.fee_rate_governor.clone();
+        bank.inflation = genesis_config.inflation;
+        bank.slots_per_year = genesis_config.slots_per_year();
+
+        bank.init()
+    }
+
+    /// Creates the bank from a `genesis.bin` created by `solana-genesis`.
+    ///
+    /// `path` can either be the genesis file or the ledger directory it's in.
+    pub fn from_genesis_file<P: AsRef<Path>>(
+        path: P,
+        faucet_keypair: Option<Keypair>,
+    ) -> error::Result<Self> {
+        let path = path.as_ref();
+        let path = if path.is_dir() {
+            path.join(DEFAULT_GENESIS_FILE)
+        } else {
+            path.to_path_buf()
+        };
+
+        let genesis_config = bincode::deserialize::<GenesisConfig>(&fs::read(path)?)?;
+        Ok(Self::from_genesis_config(&genesis_config, faucet_keypair))
+    }
+
+    fn empty(accounts: BankAccounts, genesis_hash: Hash) -> Self {
+        Self {
             accounts,
+            owner_index: HashMap::new(),
+            capitalization: 0,
             txs: HashMap::new(),
+            address_signatures: HashMap::new(),
+            blocks: BTreeMap::new(),
+            slot_signatures: vec![],
             slot: 0,
             block_height: 0,
+            transaction_count: 0,
             genesis_hash,
             latest_blockhash: genesis_hash,
+            creation_time: 0,
+            rent: Rent::default(),
+            epoch_schedule: EpochSchedule::default(),
+            fee_rate_governor: Self::default_fee_rate_governor(),
+            inflation: Inflation::default(),
+            slots_per_year: Self::default_slots_per_year(),
             builtin_programs: vec![],
             sysvar_cache: RwLock::new(SysvarCache::default()),
-            feature_set: Rc::new(FeatureSet::default()),
-        };
+            feature_set: Arc::new(FeatureSet::default()),
+            account_source: None,
+            observers: vec![],
+            account_decoders: AccountDecoders::default(),
+            faucet: Faucet::default(),
+            blockhash_queue: BlockhashQueue::default(),
+            commitment: CommitmentTracker::default(),
+            block_production: BlockProduction::default(),
+            log_limit: LogLimit::default(),
+            bpf_tracing: BpfTracing::default(),
+        }
+    }
 
-        bank.init()
+    /// Playnet doesn't charge fees unless configured otherwise
+    fn default_fee_rate_governor() -> FeeRateGovernor {
+        FeeRateGovernor::new(0, 0)
     }
 
-    fn init(mut self) -> Self {
+    fn default_slots_per_year() -> f64 {
+        years_as_slots(
+            1.0,
+            &PohConfig::default().target_tick_duration,
+            DEFAULT_TICKS_PER_SLOT,
+        )
+    }
+
+    pub(super) fn init(mut self) -> Self {
+        // Index the existing accounts
+        self.owner_index.clear();
+        self.capitalization = 0;
+        for (pubkey, account) in &self.accounts {
+            self.capitalization = self.capitalization.saturating_add(account.lamports);
+            if account.lamports != 0 {
+                self.owner_index
+                    .entry(account.owner)
+                    .or_default()
+                    .insert(*pubkey);
+            }
+        }
+
         // Add native accounts
         let mut add_native_programs = |program_id: Pubkey| {
             let mut account = Account::new(1, 0, &native_loader::id());
             account.set_executable(true);
-            self.accounts.insert(program_id, account);
+            self.store_account(program_id, account);
         };
 
         add_native_programs(bpf_loader::id());
         add_native_programs(bpf_loader_upgradeable::id());
         add_native_programs(system_program::id());
+        add_native_programs(memo_program::id());
+
+        // Fund the faucet, also for the banks that were created before it existed.
+        // Banks from before the faucet keypair got persisted have their faucet
+        // derived from a public seed, its lamports move to the new faucet.
+        let faucet_pubkey = self.faucet.pubkey();
+        if !self.accounts.contains_key(&faucet_pubkey) {
+            let legacy_pubkey = Faucet::legacy_pubkey();
+            let legacy_lamports = self
+                .accounts
+                .get(&legacy_pubkey)
+                .map(|account| account.lamports);
+            if legacy_lamports.is_some() {
+                self.store_account(legacy_pubkey, Account::default());
+            }
 
-        // Add sysvar accounts
-        fn add_sysvar_account<S: Sysvar>(bank: &mut PgBank) -> S {
-            let default = S::default();
-            let mut account = Account::new(
-                1,
-                bincode::serialized_size(&default).unwrap() as usize,
-                &sysvar::id(),
+            self.store_account(
+                faucet_pubkey,
+                Account::new(
+                    legacy_lamports.unwrap_or(FAUCET_LAMPORTS),
+                    0,
+                    &system_program::id(),
+                ),
             );
-            to_account(&default, &mut account).unwrap();
-            bank.accounts.insert(S::id(), account);
+        }
+
+        // Fee rate governor's current fee doesn't get serialized
+        self.fee_rate_governor = FeeRateGovernor::new_derived(&self.fee_rate_governor, 0);
+
+        // Banks from before the blockhash queue existed only have the latest
+        // blockhash
+        if self.blockhash_queue.is_empty() {
+            self.blockhash_queue.register(self.slot, self.latest_blockhash);
+        }
 
-            default
+        // Add sysvar accounts
+        fn add_sysvar_account<S: Sysvar>(bank: &mut PgBank, sysvar: &S) {
+            let mut account = Account::new(1, S::size_of(), &sysvar::id());
+            to_account(sysvar, &mut account).unwrap();
+            bank.store_account(S::id(), account);
         }
 
-        let clock = add_sysvar_account::<Clock>(&mut self);
-        let rent = add_sysvar_account::<Rent>(&mut self);
+        let clock = self.clock_at(self.slot);
+        let rent = self.rent;
+        let epoch_schedule = self.epoch_schedule;
+        let slot_hashes = self.blockhash_queue.slot_hashes();
+        let recent_blockhashes = self
+            .blockhash_queue
+            .recent_blockhashes(self.get_lamports_per_signature());
+        add_sysvar_account(&mut self, &clock);
+        add_sysvar_account(&mut self, &rent);
+        add_sysvar_account(&mut self, &epoch_schedule);
+        add_sysvar_account(&mut self, &slot_hashes);
+        add_sysvar_account(&mut self, &recent_blockhashes);
         let mut sysvar_cache = self.sysvar_cache.write().unwrap();
         sysvar_cache.set_clock(clock);
         sysvar_cache.set_rent(rent);
+        sysvar_cache.set_epoch_schedule(epoch_schedule);
+        sysvar_cache.set_slot_hashes(slot_hashes);
+        #[allow(deprecated)]
+        sysvar_cache.set_recent_blockhashes(recent_blockhashes);
         drop(sysvar_cache);
 
         // Add builtin programs
         self.builtin_programs = vec![
             BuiltinProgram {
                 program_id: bpf_loader::id(),
-                process_instruction: process_bpf_loader_instruction,
+                process_instruction: |first_instruction_account, invoke_context| {
+                    record_invocation(
+                        bpf_loader::id(),
+                        first_instruction_account,
+                        invoke_context,
+                        process_bpf_loader_instruction,
+                    )
+                },
             },
             BuiltinProgram {
                 program_id: bpf_loader_upgradeable::id(),
-                process_instruction: process_bpf_loader_instruction,
+                process_instruction: |first_instruction_account, invoke_context| {
+                    record_invocation(
+                        bpf_loader_upgradeable::id(),
+                        first_instruction_account,
+                        invoke_context,
+                        process_bpf_loader_instruction,
+                    )
+                },
             },
             BuiltinProgram {
                 program_id: system_program::id(),
-                process_instruction: process_system_instruction,
+                process_instruction: |first_instruction_account, invoke_context| {
+                    record_invocation(
+                        system_program::id(),
+                        first_instruction_account,
+                        invoke_context,
+                        process_system_instruction,
+                    )
+                },
+            },
+            BuiltinProgram {
+                program_id: memo_program::id(),
+                process_instruction: |first_instruction_account, invoke_context| {
+                    record_invocation(
+                        memo_program::id(),
+                        first_instruction_account,
+                        invoke_context,
+                        process_memo_instruction,
+                    )
+                },
             },
         ];
 
         // Feature set
-        self.feature_set = Rc::new(FeatureSet::default());
+        self.feature_set = Arc::new(FeatureSet::default());
 
         self
     }
 
     pub fn add_account(&mut self, key: &Pubkey, account: &Account) {
-        self.accounts.insert(key.clone(), account.clone());
+        self.store_account(*key, account.clone());
     }
 
     pub fn add_builtin(&mut self, name: &str, program_id: &Pubkey, instructions: ProcessInstructionWithContext) {
@@ -207,6 +583,182 @@ impl PgBank {
         self.add_account(program_id, &Account::from(account));
     }
 
+    /// Loads the accounts from an account dump file or a directory of account
+    /// dumps in the format of `solana account <pubkey> --output json`.
+    ///
+    /// Returns the number of loaded accounts.
+    pub fn load_accounts<P: AsRef<Path>>(&mut self, path: P) -> error::Result<usize> {
+        let accounts = if path.as_ref().is_dir() {
+            read_account_dir(path)?
+        } else {
+            vec![read_account_file(path)?]
+        };
+
+        let count = accounts.len();
+        for (pubkey, account) in accounts {
+            self.set_account(pubkey, account);
+        }
+
+        Ok(count)
+    }
+
+    /// Exports the accounts to the given directory as `<pubkey>.json` account
+    /// dumps that `solana-test-validator --account-dir` can load.
+    ///
+    /// Native programs and sysvars are skipped since validators create them.
+    /// Returns the number of exported accounts.
+    pub fn export_accounts<P: AsRef<Path>>(&self, dir: P) -> error::Result<usize> {
+        fs::create_dir_all(&dir)?;
+
+        let mut count = 0;
+        for (pubkey, account) in &self.accounts {
+            if native_loader::check_id(&account.owner) || sysvar::check_id(&account.owner) {
+                continue;
+            }
+
+            write_account_file(
+                dir.as_ref().join(format!("{}.json", pubkey)),
+                pubkey,
+                account,
+            )?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Sets the provider to consult for accounts that don't exist in the bank
+    pub fn set_account_source(&mut self, account_source: Box<dyn AccountSource>) {
+        self.account_source = Some(account_source);
+    }
+
+    /// Registers a hook to notify of the committed state changes
+    pub fn add_observer(&mut self, observer: Arc<dyn BankObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Registers a decoder for the accounts owned by `owner`, it takes
+    /// precedence over the builtin decoders
+    pub fn add_account_decoder(&mut self, owner: Pubkey, decoder: Box<dyn AccountDecoder>) {
+        self.account_decoders.register(owner, decoder);
+    }
+
+    pub fn get_commitment_config(&self) -> CommitmentConfig {
+        self.commitment.config()
+    }
+
+    pub fn set_commitment_config(&mut self, config: CommitmentConfig) {
+        self.commitment.set_config(config);
+    }
+
+    /// Returns the highest slot that reached the commitment level, `None` if
+    /// no slot has reached it yet
+    pub fn get_commitment_slot(&self, commitment: CommitmentLevel) -> Option<Slot> {
+        self.commitment.get_commitment_slot(commitment, self.slot)
+    }
+
+    /// Returns the commitment level the slot has reached
+    pub fn get_commitment_level(&self, slot: Slot) -> CommitmentLevel {
+        self.commitment.get_commitment_level(slot, self.slot)
+    }
+
+    /// Returns the account as of the highest slot that reached the commitment
+    /// level. The account source is not consulted.
+    pub fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentLevel,
+    ) -> Option<&Account> {
+        let slot = self.get_commitment_slot(commitment);
+        match self.commitment.get_account_at(pubkey, slot) {
+            Some(account) => account,
+            None => self.get_account(pubkey),
+        }
+    }
+
+    pub fn get_block_production(&self) -> BlockProduction {
+        self.block_production
+    }
+
+    /// Changes when the slots end, the current slot ends on its next
+    /// transaction if it already has enough transactions for the new mode
+    pub fn set_block_production(&mut self, block_production: BlockProduction) {
+        self.block_production = block_production;
+    }
+
+    /// Ends the given number of slots, including the current slot
+    pub fn advance_slots(&mut self, count: u64) {
+        for _ in 0..count {
+            self.new_slot();
+        }
+    }
+
+    pub fn get_log_limit(&self) -> LogLimit {
+        self.log_limit
+    }
+
+    pub fn set_log_limit(&mut self, log_limit: LogLimit) {
+        self.log_limit = log_limit;
+    }
+
+    pub fn get_bpf_tracing(&self) -> &BpfTracing {
+        &self.bpf_tracing
+    }
+
+    pub fn set_bpf_tracing(&mut self, bpf_tracing: BpfTracing) {
+        self.bpf_tracing = bpf_tracing;
+    }
+
+    pub fn get_faucet_pubkey(&self) -> Pubkey {
+        self.faucet.pubkey()
+    }
+
+    pub fn set_airdrop_limits(&mut self, limits: AirdropLimits) {
+        self.faucet.set_limits(limits);
+    }
+
+    /// Returns `None` if there is no decoder for the account data
+    pub fn decode_account(&self, pubkey: &Pubkey, account: &Account) -> Option<ParsedAccount> {
+        self.account_decoders.decode(pubkey, account, self)
+    }
+
+    fn notify(&self, event: BankEvent) {
+        for observer in &self.observers {
+            observer.on_event(&event);
+        }
+    }
+
+    /// Address loader for sanitizing the v0 messages against the bank
+    pub fn address_loader(&self) -> PgAddressLoader<'_> {
+        PgAddressLoader { bank: self }
+    }
+
+    fn load_lookup_table_addresses(
+        &self,
+        lookup: &MessageAddressTableLookup,
+    ) -> Result<LoadedAddresses, AddressLookupError> {
+        let table_account = self
+            .get_account(&lookup.account_key)
+            .ok_or(AddressLookupError::LookupTableAccountNotFound)?;
+        if table_account.owner != solana_address_lookup_table_program::id() {
+            return Err(AddressLookupError::InvalidAccountOwner);
+        }
+
+        let slot_hashes = self.sysvar_cache.read().unwrap().get_slot_hashes().unwrap();
+        let lookup_table = AddressLookupTable::deserialize(&table_account.data)
+            .map_err(|_| AddressLookupError::InvalidAccountData)?;
+        Ok(LoadedAddresses {
+            writable: lookup_table.lookup(self.slot, &lookup.writable_indexes, &slot_hashes)?,
+            readonly: lookup_table.lookup(self.slot, &lookup.readonly_indexes, &slot_hashes)?,
+        })
+    }
+
+    /// Whether the bank can fetch the accounts it doesn't have, which requires
+    /// mutable access to cache them
+    pub fn has_account_source(&self) -> bool {
+        self.account_source.is_some()
+    }
+
     pub fn get_slot(&self) -> Slot {
         self.slot
     }
@@ -216,19 +768,64 @@ impl PgBank {
     }
 
     pub fn get_genesis_hash(&self) -> Hash {
-        self.latest_blockhash
+        self.genesis_hash
     }
 
     pub fn get_latest_blockhash(&self) -> Hash {
         self.latest_blockhash
     }
 
+    /// Whether the blockhash is recent enough for transactions to use it
+    pub fn is_blockhash_valid(&self, blockhash: &Hash) -> bool {
+        self.blockhash_queue.is_valid(blockhash)
+    }
+
     pub fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> u64 {
-        Rent::default().minimum_balance(data_len).max(1)
+        self.rent.minimum_balance(data_len).max(1)
     }
 
-    pub fn feature_set(&self) -> &FeatureSet {
-        &*self.feature_set
+    pub fn get_epoch_info(&self) -> EpochInfo {
+        let (epoch, slot_index) = self.epoch_schedule.get_epoch_and_slot_index(self.slot);
+        EpochInfo {
+            epoch,
+            slot_index,
+            slots_in_epoch: self.epoch_schedule.get_slots_in_epoch(epoch),
+            absolute_slot: self.slot,
+            block_height: self.block_height,
+            transaction_count: Some(self.transaction_count),
+        }
+    }
+
+    pub fn get_epoch_schedule(&self) -> &EpochSchedule {
+        &self.epoch_schedule
+    }
+
+    /// Total lamports of all accounts
+    pub fn get_capitalization(&self) -> u64 {
+        self.capitalization
+    }
+
+    pub fn get_inflation(&self) -> &Inflation {
+        &self.inflation
+    }
+
+    /// Years passed since genesis at the start of the current epoch, used for
+    /// calculating the inflation rates
+    pub fn get_slot_in_year_for_inflation(&self) -> f64 {
+        let epoch = self.epoch_schedule.get_epoch(self.slot);
+        self.epoch_schedule.get_first_slot_in_epoch(epoch) as f64 / self.slots_per_year
+    }
+
+    /// Identity of the node producing the blocks.
+    ///
+    /// Playnet has no validator keypair, the identity is derived from the
+    /// genesis hash so that it's stable for the same bank.
+    pub fn get_identity(&self) -> Pubkey {
+        Pubkey::new_from_array(hashv(&[self.genesis_hash.as_ref(), b"identity"]).to_bytes())
+    }
+
+    pub fn feature_set(&self) -> &Arc<FeatureSet> {
+        &self.feature_set
     }
 
     /// Returns `None` for accounts with 0 lamports
@@ -236,7 +833,27 @@ impl PgBank {
         self.accounts.get(pubkey)
     }
 
-    /// Returns `Account::default` for 0 lamports account
+    /// Returns the accounts owned by the program that match all the filters.
+    ///
+    /// Accounts with 0 lamports are not included.
+    pub fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[AccountFilter],
+    ) -> Vec<(Pubkey, &Account)> {
+        self.owner_index
+            .get(program_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|pubkey| Some((*pubkey, self.accounts.get(pubkey)?)))
+            .filter(|(_, account)| filters.iter().all(|filter| filter.matches(account)))
+            .collect()
+    }
+
+    /// Returns `Account::default` for 0 lamports account.
+    ///
+    /// The account source is not consulted, use `fetch_account` to get the
+    /// account from it.
     pub fn get_account_default(&self, pubkey: &Pubkey) -> Account {
         match self.accounts.get(pubkey) {
             Some(account) => account.to_owned(),
@@ -244,15 +861,99 @@ impl PgBank {
         }
     }
 
+    /// Returns the account from the bank, fetching it from the account source
+    /// and caching it in the bank if it doesn't exist.
+    pub fn fetch_account(&mut self, pubkey: &Pubkey) -> Option<&Account> {
+        if !self.accounts.contains_key(pubkey) {
+            let account = self
+                .account_source
+                .as_ref()
+                .and_then(|source| source.get_account(pubkey));
+            trace!(%pubkey, found = account.is_some(), "Fetched account from account source");
+            self.store_account(*pubkey, account?);
+        }
+
+        self.accounts.get(pubkey)
+    }
+
+    /// Fetches all accounts the transaction references, including the accounts
+    /// needed to execute its programs, from the account source.
+    pub fn fetch_tx_accounts(&mut self, tx: &SanitizedTransaction) {
+        if self.account_source.is_none() {
+            return;
+        }
+
+        let mut pubkeys = tx.message().account_keys().iter().copied().collect::<Vec<_>>();
+        while let Some(pubkey) = pubkeys.pop() {
+            let (owner, programdata_address) = match self.fetch_account(&pubkey) {
+                Some(account) if account.executable => match account.state() {
+                    Ok(UpgradeableLoaderState::Program {
+                        programdata_address,
+                    }) if bpf_loader_upgradeable::check_id(&account.owner) => {
+                        (account.owner, Some(programdata_address))
+                    }
+                    _ => (account.owner, None),
+                },
+                _ => continue,
+            };
+
+            // Load the program chain
+            if let Some(programdata_address) = programdata_address {
+                self.fetch_account(&programdata_address);
+            } else if !native_loader::check_id(&owner) && !self.accounts.contains_key(&owner) {
+                pubkeys.push(owner);
+            }
+        }
+    }
+
     /// Inserts the account if it doesn't exist or updates the existing account.
     /// Previous value or `None` is returned for initial insertion.
     pub fn set_account(&mut self, pubkey: Pubkey, account: Account) -> Option<Account> {
-        self.accounts.insert(pubkey, account)
+        self.notify(BankEvent::AccountChanged {
+            pubkey: &pubkey,
+            account: &account,
+            slot: self.slot,
+        });
+        let previous = self.store_account(pubkey, account);
+        self.commitment.record_account_change(self.slot, pubkey, &previous);
+        previous
+    }
+
+    /// Stores the account without notifying the observers, keeping the owner
+    /// index in sync
+    fn store_account(&mut self, pubkey: Pubkey, account: Account) -> Option<Account> {
+        self.capitalization = self.capitalization.saturating_add(account.lamports);
+        if account.lamports != 0 {
+            self.owner_index
+                .entry(account.owner)
+                .or_default()
+                .insert(pubkey);
+        }
+
+        let previous = self.accounts.insert(pubkey, account);
+        if let Some(previous) = &previous {
+            self.capitalization = self.capitalization.saturating_sub(previous.lamports);
+            let account = &self.accounts[&pubkey];
+            if previous.owner != account.owner || account.lamports == 0 {
+                if let Some(pubkeys) = self.owner_index.get_mut(&previous.owner) {
+                    pubkeys.remove(&pubkey);
+                    if pubkeys.is_empty() {
+                        self.owner_index.remove(&previous.owner);
+                    }
+                }
+            }
+        }
+
+        previous
     }
 
     pub fn get_fee_for_message(&self, msg: &SanitizedMessage) -> Option<u64> {
         (msg.header().num_required_signatures.max(1) as u64)
-            .checked_mul(PgBank::LAMPORTS_PER_SIGNATURE)
+            .checked_mul(self.get_lamports_per_signature())
+    }
+
+    pub fn get_lamports_per_signature(&self) -> u64 {
+        self.fee_rate_governor.lamports_per_signature
     }
 
     fn get_num_signatures_in_message(message: &SanitizedMessage) -> u64 {
@@ -328,32 +1029,60 @@ impl PgBank {
     }
 
     pub fn simulate_tx(&self, tx: &SanitizedTransaction) -> SimulateTransactionResult {
+        self.simulate_tx_with_overrides(tx, &StateOverrides::default(), self.log_limit)
+    }
+
+    /// Simulates the transaction as if the accounts had the overridden state,
+    /// the bank is not mutated. The accounts of the transaction have to be
+    /// fetched from the account source with `fetch_tx_accounts` beforehand.
+    pub fn simulate_tx_with_overrides(
+        &self,
+        tx: &SanitizedTransaction,
+        overrides: &StateOverrides,
+        log_limit: LogLimit,
+    ) -> SimulateTransactionResult {
         // let fee = calculate_fee(tx.message());
-        let mut loaded_tx = match self.load_tx(tx) {
+        let mut loaded_tx = match self.load_tx(tx, overrides) {
             Ok(loaded_tx) => loaded_tx,
-            Err(err) => return SimulateTransactionResult::new_error(err),
+            Err(err) => {
+                debug!(signature = %tx.signature(), %err, "Failed to load transaction");
+                return SimulateTransactionResult::new_error(err);
+            }
         };
 
         let account_count = tx.message().account_keys().len();
-        let pre_accounts = loaded_tx
+        let mut pre_accounts = loaded_tx
             .accounts
             .clone()
             .into_iter()
             .take(account_count)
             .collect::<Vec<TransactionAccount>>();
 
-        match self.execute_loaded_tx(&tx, &mut loaded_tx) {
+        // Pre balances are before the fee gets charged from the fee payer
+        if let Some((_, fee_payer)) = pre_accounts.first_mut() {
+            fee_payer.set_lamports(fee_payer.lamports().saturating_add(self.get_tx_fee(tx)));
+        }
+
+        let (execution_result, invocations) =
+            self.execute_loaded_tx(&tx, &mut loaded_tx, log_limit);
+        match execution_result {
             TransactionExecutionResult::Executed {
                 details,
                 tx_executor_cache: _,
-            } => SimulateTransactionResult::new(
-                details.status,
-                pre_accounts,
-                loaded_tx.accounts.into_iter().take(account_count).collect(),
-                details.log_messages.unwrap_or_default(),
-                details.executed_units,
-                details.return_data,
-            ),
+            } => {
+                let mut result = SimulateTransactionResult::new(
+                    details.status,
+                    pre_accounts,
+                    loaded_tx.accounts.into_iter().take(account_count).collect(),
+                    details.log_messages.unwrap_or_default(),
+                    details.executed_units,
+                    details.return_data,
+                    details.inner_instructions.map(compile_inner_instructions),
+                );
+                result.invocations = invocations;
+                result.program_details = details.program_details;
+                result
+            }
             TransactionExecutionResult::NotExecuted(err) => {
                 SimulateTransactionResult::new_error(err)
             }
@@ -361,31 +1090,231 @@ impl PgBank {
     }
 
     pub fn process_tx(&mut self, tx: SanitizedTransaction) -> transaction::Result<Signature> {
+        // Check before committing anything
+        if self.txs.contains_key(tx.signature()) {
+            return Err(TransactionError::AlreadyProcessed);
+        }
+
+        self.fetch_tx_accounts(&tx);
         let simulation_result = self.simulate_tx(&tx);
         match simulation_result.result {
             Ok(_) => {
-                // TODO: Substract the fee from the `fee_payer`
-                let fee = self.get_fee_for_message(tx.message()).unwrap();
+                // Fee is already charged from the fee payer in `post_accounts`
+                let fee = self.get_tx_fee(&tx);
 
-                for (pubkey, account) in &simulation_result.post_accounts {
-                    self.set_account(pubkey.clone(), account.clone().into());
+                // Read-only accounts can't change
+                for (index, (pubkey, account)) in
+                    simulation_result.post_accounts.iter().enumerate()
+                {
+                    if tx.message().is_writable(index) {
+                        self.set_account(*pubkey, account.clone().into());
+                    }
                 }
 
+                self.notify(BankEvent::TransactionCommitted {
+                    signature: tx.signature(),
+                    message: tx.message(),
+                    err: &None,
+                    logs: &simulation_result.logs,
+                    slot: self.slot,
+                });
+
                 let tx_hash = self.save_tx(tx, simulation_result, fee)?;
+                debug!(signature = %tx_hash, fee, slot = self.slot, "Committed transaction");
                 Ok(tx_hash)
             }
             Err(err) => Err(err),
         }
     }
 
+    /// Airdrops with a system transfer from the faucet, returns the signature
+    /// of the transfer
+    pub fn request_airdrop(&mut self, to: &Pubkey, lamports: u64) -> error::Result<Signature> {
+        self.faucet.check_limits(to, lamports)?;
+
+        let tx = SanitizedTransaction::try_from_legacy_transaction(
+            self.faucet.create_airdrop_tx(to, lamports, self.latest_blockhash),
+        )?;
+        let balance = self
+            .get_account(&self.faucet.pubkey())
+            .map(|account| account.lamports)
+            .unwrap_or_default();
+        if balance < lamports.saturating_add(self.get_tx_fee(&tx)) {
+            return Err(error::PlaynetError::FaucetExhausted { lamports, balance });
+        }
+
+        let signature = self
+            .process_tx(tx)
+            .map_err(error::PlaynetError::AirdropFailed)?;
+        self.faucet.record_airdrop(to, lamports);
+        Ok(signature)
+    }
+
     pub fn get_tx(&self, signature: &Signature) -> Option<&TransactionData> {
         self.txs.get(signature)
     }
 
+    /// Returns the transactions that reference the address, newest first.
+    ///
+    /// Transactions start after `before` and end before `until`, transactions
+    /// that haven't reached the commitment level are skipped. Nothing is
+    /// returned if `before` doesn't exist.
+    pub fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<&Signature>,
+        until: Option<&Signature>,
+        limit: usize,
+        commitment: CommitmentLevel,
+    ) -> Vec<(&Signature, &TransactionData)> {
+        let before = match before {
+            Some(before) => match self.txs.get(before) {
+                Some(tx_data) => Some((before, tx_data.get_slot())),
+                None => return vec![],
+            },
+            None => None,
+        };
+        let until = until.and_then(|until| Some((until, self.txs.get(until)?.get_slot())));
+
+        self.address_signatures
+            .get(address)
+            .into_iter()
+            .flatten()
+            .rev()
+            .filter_map(|signature| Some((signature, self.txs.get(signature)?)))
+            .skip_while(|(signature, tx_data)| match before {
+                Some((before, slot)) => *signature != before && tx_data.get_slot() >= slot,
+                None => false,
+            })
+            .filter(|(signature, _)| before.map(|(before, _)| *signature != before).unwrap_or(true))
+            .filter(|(_, tx_data)| self.get_commitment_level(tx_data.get_slot()) >= commitment)
+            .take_while(|(signature, tx_data)| match until {
+                Some((until, slot)) => *signature != until && tx_data.get_slot() >= slot,
+                None => true,
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// Returns the produced block at the given slot
+    pub fn get_block(&self, slot: Slot) -> Option<&Block> {
+        self.blocks.get(&slot)
+    }
+
+    /// Returns the slots of the produced blocks in the given inclusive range
+    pub fn get_blocks(&self, start_slot: Slot, end_slot: Slot) -> Vec<Slot> {
+        if end_slot < start_slot {
+            return vec![];
+        }
+
+        self.blocks
+            .range(start_slot..=end_slot)
+            .map(|(slot, _)| *slot)
+            .collect()
+    }
+
+    /// Returns the slots of at most `limit` produced blocks starting at `start_slot`
+    pub fn get_blocks_with_limit(&self, start_slot: Slot, limit: usize) -> Vec<Slot> {
+        self.blocks
+            .range(start_slot..)
+            .take(limit)
+            .map(|(slot, _)| *slot)
+            .collect()
+    }
+
+    /// Returns the slot of the oldest block the bank has
+    pub fn get_first_available_block(&self) -> Slot {
+        self.blocks
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(self.slot)
+    }
+
     fn new_slot(&mut self) {
+        // Record the block of the finished slot
+        let previous_blockhash = self.latest_blockhash;
         self.latest_blockhash = create_blockhash(&self.latest_blockhash.to_bytes());
+        let block_time = self
+            .sysvar_cache
+            .read()
+            .unwrap()
+            .get_clock()
+            .ok()
+            .map(|clock| clock.unix_timestamp);
+        self.blocks.insert(
+            self.slot,
+            Block {
+                parent_slot: self.slot.saturating_sub(1),
+                blockhash: self.latest_blockhash,
+                previous_blockhash,
+                block_time,
+                block_height: self.block_height,
+                signatures: mem::take(&mut self.slot_signatures),
+            },
+        );
+
+        self.commitment.on_slot_finished(self.slot);
         self.slot += 1;
         self.block_height += 1;
+        self.blockhash_queue.register(self.slot, self.latest_blockhash);
+
+        let clock = self.clock_at(self.slot);
+        let slot_hashes = self.blockhash_queue.slot_hashes();
+        let recent_blockhashes = self
+            .blockhash_queue
+            .recent_blockhashes(self.get_lamports_per_signature());
+        self.set_sysvar_account(&clock);
+        self.set_sysvar_account(&slot_hashes);
+        self.set_sysvar_account(&recent_blockhashes);
+        let mut sysvar_cache = self.sysvar_cache.write().unwrap();
+        sysvar_cache.set_clock(clock);
+        sysvar_cache.set_slot_hashes(slot_hashes);
+        #[allow(deprecated)]
+        sysvar_cache.set_recent_blockhashes(recent_blockhashes);
+        drop(sysvar_cache);
+
+        debug!(
+            slot = self.slot,
+            block_height = self.block_height,
+            blockhash = %self.latest_blockhash,
+            "New slot"
+        );
+        let finalized = self.get_commitment_slot(CommitmentLevel::Finalized);
+        self.notify(BankEvent::NewSlot {
+            slot: self.slot,
+            parent: self.slot - 1,
+            root: finalized.unwrap_or_default(),
+            confirmed: self.get_commitment_slot(CommitmentLevel::Confirmed),
+            finalized,
+        });
+    }
+
+    /// Updates the sysvar account, notifying the observers
+    fn set_sysvar_account<S: Sysvar>(&mut self, sysvar: &S) {
+        let mut account = self.get_account_default(&S::id());
+        to_account(sysvar, &mut account).unwrap();
+        self.set_account(S::id(), account);
+    }
+
+    /// Returns the `Clock` of the slot, time passes by the slot duration
+    /// derived from the slots per year
+    fn clock_at(&self, slot: Slot) -> Clock {
+        let slot_duration = slot_duration_from_slots_per_year(self.slots_per_year);
+        let timestamp_at = |slot: Slot| {
+            let elapsed = slot_duration.as_nanos().saturating_mul(slot as u128) / 1_000_000_000;
+            self.creation_time
+                .saturating_add(elapsed.try_into().unwrap_or(UnixTimestamp::MAX))
+        };
+        let epoch = self.epoch_schedule.get_epoch(slot);
+
+        Clock {
+            slot,
+            epoch_start_timestamp: timestamp_at(self.epoch_schedule.get_first_slot_in_epoch(epoch)),
+            epoch,
+            leader_schedule_epoch: self.epoch_schedule.get_leader_schedule_epoch(slot),
+            unix_timestamp: timestamp_at(slot),
+        }
     }
 
     fn save_tx(
@@ -420,6 +1349,15 @@ impl PgBank {
             Some(_) => Err(TransactionError::AlreadyProcessed),
             None => {
                 let signature = signature.to_owned();
+                for pubkey in tx.message().account_keys().iter() {
+                    self.address_signatures
+                        .entry(*pubkey)
+                        .or_default()
+                        .push(signature);
+                }
+                self.slot_signatures.push(signature);
+                self.transaction_count += 1;
+
                 self.txs.insert(
                     signature,
                     TransactionData::new(
@@ -427,8 +1365,7 @@ impl PgBank {
                         tx.to_versioned_transaction(),
                         Some(ConfirmedTransactionMeta {
                             fee,
-                            // TODO:
-                            inner_instructions: None,
+                            inner_instructions: result.inner_instructions,
                             pre_balances: result
                                 .pre_accounts
                                 .iter()
@@ -445,8 +1382,21 @@ impl PgBank {
                             // TODO:
                             post_token_balances: None,
                             err: result.result.err(),
-                            loaded_addresses: None,
+                            loaded_addresses: match tx.message() {
+                                SanitizedMessage::V0(message) => {
+                                    Some(message.loaded_addresses.clone().into_owned())
+                                }
+                                SanitizedMessage::Legacy(_) => None,
+                            },
                             compute_units_consumed: Some(result.units_consumed),
+                            instruction_compute_units: Some(
+                                result
+                                    .invocations
+                                    .iter()
+                                    .map(InstructionComputeUnits::from)
+                                    .collect(),
+                            ),
+                            program_details: Some(result.program_details),
                         }),
                         Some(
                             self.sysvar_cache
@@ -458,25 +1408,66 @@ impl PgBank {
                         ),
                     ),
                 );
-                self.new_slot();
+                if self.block_production.ends_slot(self.slot_signatures.len()) {
+                    self.new_slot();
+                }
 
                 Ok(signature)
             }
         }
     }
 
-    fn load_tx(&self, tx: &SanitizedTransaction) -> transaction::Result<LoadedTransaction> {
-        let fee = 0;
+    fn get_tx_fee(&self, tx: &SanitizedTransaction) -> u64 {
+        self.get_fee_for_message(tx.message()).unwrap_or(u64::MAX)
+    }
+
+    fn load_tx(
+        &self,
+        tx: &SanitizedTransaction,
+        overrides: &StateOverrides,
+    ) -> transaction::Result<LoadedTransaction> {
+        self.check_blockhash(tx, overrides)?;
+
+        let fee = self.get_tx_fee(tx);
         let mut error_counters = TransactionErrorMetrics::default();
         let feature_set = FeatureSet::default();
-        self.load_tx_accounts(&tx, fee, &mut error_counters, &feature_set)
+        self.load_tx_accounts(&tx, fee, &mut error_counters, &feature_set, overrides)
+    }
+
+    /// Checks that the transaction uses a recent blockhash or the durable nonce
+    /// of its nonce account
+    fn check_blockhash(
+        &self,
+        tx: &SanitizedTransaction,
+        overrides: &StateOverrides,
+    ) -> transaction::Result<()> {
+        let recent_blockhash = tx.message().recent_blockhash();
+        if self.is_blockhash_valid(recent_blockhash) {
+            return Ok(());
+        }
+
+        tx.get_durable_nonce()
+            .and_then(|nonce_pubkey| self.get_overridden_account(nonce_pubkey, overrides))
+            .and_then(|account| verify_nonce_account(&account.into(), recent_blockhash))
+            .map(|_| ())
+            .ok_or(TransactionError::BlockhashNotFound)
+    }
+
+    /// Returns the bank account with the `overrides` applied
+    fn get_overridden_account(
+        &self,
+        pubkey: &Pubkey,
+        overrides: &StateOverrides,
+    ) -> Option<Account> {
+        overrides.apply(pubkey, self.get_account(pubkey).cloned())
     }
 
     fn execute_loaded_tx(
         &self,
         tx: &SanitizedTransaction,
         loaded_tx: &mut LoadedTransaction,
-    ) -> TransactionExecutionResult {
+        log_limit: LogLimit,
+    ) -> (TransactionExecutionResult, Vec<Invocation>) {
         let compute_budget = ComputeBudget::default();
         let mut transaction_context = TransactionContext::new(
             loaded_tx.accounts.clone(),
@@ -485,17 +1476,17 @@ impl PgBank {
             compute_budget.max_invoke_depth,
         );
 
-        let log_collector = Rc::new(RefCell::new(LogCollector::default()));
+        let log_collector = LogCollector::new_ref_with_limit(log_limit.bytes());
         let tx_executor_cache = Rc::new(RefCell::new(Executors::default()));
-        let feature_set = Arc::new(FeatureSet::default());
+        let feature_set = Arc::clone(&self.feature_set);
         let mut timings = ExecuteTimings::default();
-        let blockhash = tx.message().recent_blockhash();
         let current_accounts_data_len = u32::MAX as u64;
         let mut accumulated_consume_units = 0;
 
         // Get sysvars
         let sysvar_cache = self.sysvar_cache.read().unwrap();
 
+        start_recording(self.bpf_tracing.is_enabled());
         let process_result = MessageProcessor::process_message(
             &self.builtin_programs,
             tx.message(),
@@ -508,15 +1499,29 @@ impl PgBank {
             compute_budget,
             &mut timings,
             &sysvar_cache,
-            *blockhash,
-            PgBank::LAMPORTS_PER_SIGNATURE,
+            self.latest_blockhash,
+            self.get_lamports_per_signature(),
             current_accounts_data_len,
             &mut accumulated_consume_units,
         );
 
+        match &process_result {
+            Ok(_) => debug!(
+                signature = %tx.signature(),
+                units_consumed = accumulated_consume_units,
+                "Executed transaction"
+            ),
+            Err(err) => debug!(
+                signature = %tx.signature(),
+                units_consumed = accumulated_consume_units,
+                %err,
+                "Transaction execution failed"
+            ),
+        }
+
         let ExecutionRecord {
             accounts,
-            instruction_trace: _,
+            instruction_trace,
             mut return_data,
             changed_account_count: _,
             total_size_of_all_accounts: _,
@@ -525,28 +1530,55 @@ impl PgBank {
         } = transaction_context.into();
         loaded_tx.accounts = accounts;
 
-        match process_result {
-            Ok(info) => TransactionExecutionResult::Executed {
-                details: TransactionExecutionDetails {
-                    status: Ok(()),
-                    log_messages: Some(log_collector.borrow().get_recorded_content().to_vec()),
-                    inner_instructions: None,
-                    durable_nonce_fee: None,
-                    return_data: match return_data.data.iter().rposition(|&x| x != 0) {
-                        Some(end_index) => {
-                            let end_index = end_index.saturating_add(1);
-                            return_data.data.truncate(end_index);
-                            Some(return_data)
-                        }
-                        None => None,
-                    },
-                    executed_units: accumulated_consume_units,
-                    accounts_data_len_delta: info.accounts_data_len_delta,
+        let log_messages = log_collector.borrow().get_recorded_content().to_vec();
+        let mut invocations = finish_recording(&log_messages);
+        // Errors that the runtime returns after the program are only in the
+        // result of the transaction
+        if let Err(TransactionError::InstructionError(index, err)) = &process_result {
+            if let Some(invocation) = invocations.get_mut(*index as usize) {
+                if invocation.result.is_ok() {
+                    invocation.result = Err(err.clone());
+                }
+            }
+        }
+
+        if let BpfTracing::Directory(dir) = &self.bpf_tracing {
+            if let Err(err) = write_traces(dir, tx.signature(), &invocations) {
+                warn!(signature = %tx.signature(), %err, "Failed to write the BPF traces");
+            }
+        }
+
+        let program_details = program_execution_details(&timings, &invocations);
+
+        // Failed executions keep their logs and invocations for debugging
+        let accounts_data_len_delta = process_result
+            .as_ref()
+            .map(|info| info.accounts_data_len_delta)
+            .unwrap_or_default();
+        let execution_result = TransactionExecutionResult::Executed {
+            details: TransactionExecutionDetails {
+                status: process_result.map(|_| ()),
+                log_messages: Some(log_messages),
+                inner_instructions: Some(inner_instructions_list_from_instruction_trace(
+                    &instruction_trace,
+                )),
+                durable_nonce_fee: None,
+                return_data: match return_data.data.iter().rposition(|&x| x != 0) {
+                    Some(end_index) => {
+                        let end_index = end_index.saturating_add(1);
+                        return_data.data.truncate(end_index);
+                        Some(return_data)
+                    }
+                    None => None,
                 },
-                tx_executor_cache,
+                executed_units: accumulated_consume_units,
+                accounts_data_len_delta,
+                program_details,
             },
-            Err(err) => TransactionExecutionResult::NotExecuted(err),
-        }
+            tx_executor_cache,
+        };
+
+        (execution_result, invocations)
     }
 
     fn load_tx_accounts(
@@ -555,6 +1587,7 @@ impl PgBank {
         fee: u64,
         error_counters: &mut TransactionErrorMetrics,
         feature_set: &FeatureSet,
+        overrides: &StateOverrides,
     ) -> transaction::Result<LoadedTransaction> {
         // NOTE: this check will never fail because `tx` is sanitized
         if tx.signatures().is_empty() && fee != 0 {
@@ -575,12 +1608,14 @@ impl PgBank {
             .iter()
             .enumerate()
             .map(|(i, pubkey)| {
-                println!("index: {}, pk: {:?}", i, pubkey);
+                trace!(index = i, %pubkey, "Loading transaction account");
                 let (account, loaded_programdata_account_size) = if !message.is_non_loader_key(i) {
                     // TODO:
                     // Fill in an empty account for the program slots.
                     // (AccountSharedData::default(), 0)
-                    let account = self.get_account_default(pubkey);
+                    let account = overrides
+                        .apply(pubkey, Some(self.get_account_default(pubkey)))
+                        .unwrap_or_default();
                     let program_len = account.data.len();
                     (AccountSharedData::from(account), program_len)
                 } else {
@@ -595,7 +1630,11 @@ impl PgBank {
                             0,
                         )
                     } else {
-                        let mut account = AccountSharedData::from(self.get_account_default(pubkey));
+                        let mut account = AccountSharedData::from(
+                            overrides
+                                .apply(pubkey, Some(self.get_account_default(pubkey)))
+                                .unwrap_or_default(),
+                        );
 
                         if !validated_fee_payer {
                             Self::validate_fee_payer(
@@ -603,6 +1642,7 @@ impl PgBank {
                                 &mut account,
                                 i,
                                 error_counters,
+                                &self.rent,
                                 feature_set,
                                 fee,
                             )?;
@@ -624,13 +1664,13 @@ impl PgBank {
                                 }) = account.state()
                                 {
                                     if let Some(programdata_account) =
-                                        self.get_account(&programdata_address)
+                                        self.get_overridden_account(&programdata_address, overrides)
                                     {
                                         loaded_programdata_account_size =
                                             programdata_account.data().len();
                                         account_deps.push((
                                             programdata_address,
-                                            AccountSharedData::from(programdata_account.to_owned()),
+                                            AccountSharedData::from(programdata_account),
                                         ));
                                     } else {
                                         error_counters.account_not_found += 1;
@@ -670,10 +1710,6 @@ impl PgBank {
         // accounts.iter().take(message.account_keys.len())
         accounts.append(&mut account_deps);
 
-        println!("accounts[0]: {:?}", accounts[0].0);
-        println!("accounts[1]: {:?}", accounts[1].0);
-        println!("accounts[2]: {:?}", accounts[2].0);
-
         if validated_fee_payer {
             let program_indices = message
                 .instructions()
@@ -685,6 +1721,7 @@ impl PgBank {
                         error_counters,
                         &mut accumulated_accounts_data_size,
                         requested_loaded_accounts_data_size_limit,
+                        overrides,
                     )
                 })
                 .collect::<transaction::Result<Vec<Vec<usize>>>>()?;
@@ -706,6 +1743,7 @@ impl PgBank {
         error_counters: &mut TransactionErrorMetrics,
         accumulated_accounts_data_size: &mut usize,
         requested_loaded_accounts_data_size_limit: Option<NonZeroUsize>,
+        overrides: &StateOverrides,
     ) -> transaction::Result<Vec<usize>> {
         let mut account_indices = Vec::new();
         let (mut program_id, already_loaded_as_non_loader) =
@@ -720,9 +1758,9 @@ impl PgBank {
                     return Err(TransactionError::ProgramAccountNotFound);
                 }
             };
-        println!("program_id: {:?}", program_id);
         let mut depth = 0;
         while !native_loader::check_id(&program_id) {
+            trace!(%program_id, depth, "Resolving program chain");
             if depth >= 5 {
                 error_counters.call_chain_too_deep += 1;
                 return Err(TransactionError::CallChainTooDeep);
@@ -730,7 +1768,7 @@ impl PgBank {
             depth += 1;
             let mut loaded_account_total_size: usize = 0;
 
-            program_account_index = match self.get_account(&program_id) {
+            program_account_index = match self.get_overridden_account(&program_id, overrides) {
                 Some(program_account) => {
                     let account_index = accounts.len();
                     // do not double count account size for program account on top of call chain
@@ -740,10 +1778,7 @@ impl PgBank {
                         loaded_account_total_size =
                             loaded_account_total_size.saturating_add(program_account.data().len());
                     }
-                    accounts.push((
-                        program_id,
-                        AccountSharedData::from(program_account.to_owned()),
-                    ));
+                    accounts.push((program_id, AccountSharedData::from(program_account)));
                     account_index
                 }
                 None => {
@@ -767,24 +1802,25 @@ impl PgBank {
                     programdata_address,
                 }) = program.state()
                 {
-                    let programdata_account_index = match self.get_account(&programdata_address) {
-                        Some(programdata_account) => {
-                            let account_index = accounts.len();
-                            if !(depth == 1 && already_loaded_as_non_loader) {
-                                loaded_account_total_size = loaded_account_total_size
-                                    .saturating_add(programdata_account.data().len());
+                    let programdata_account_index =
+                        match self.get_overridden_account(&programdata_address, overrides) {
+                            Some(programdata_account) => {
+                                let account_index = accounts.len();
+                                if !(depth == 1 && already_loaded_as_non_loader) {
+                                    loaded_account_total_size = loaded_account_total_size
+                                        .saturating_add(programdata_account.data().len());
+                                }
+                                accounts.push((
+                                    programdata_address,
+                                    AccountSharedData::from(programdata_account),
+                                ));
+                                account_index
                             }
-                            accounts.push((
-                                programdata_address,
-                                AccountSharedData::from(programdata_account.to_owned()),
-                            ));
-                            account_index
-                        }
-                        None => {
-                            error_counters.account_not_found += 1;
-                            return Err(TransactionError::ProgramAccountNotFound);
-                        }
-                    };
+                            None => {
+                                error_counters.account_not_found += 1;
+                                return Err(TransactionError::ProgramAccountNotFound);
+                            }
+                        };
                     account_indices.insert(0, programdata_account_index);
                 } else {
                     error_counters.invalid_program_for_execution += 1;
@@ -825,6 +1861,7 @@ impl PgBank {
         payer_account: &mut AccountSharedData,
         _payer_index: usize,
         error_counters: &mut TransactionErrorMetrics,
+        rent: &Rent,
         _feature_set: &FeatureSet,
         fee: u64,
     ) -> transaction::Result<()> {
@@ -837,15 +1874,17 @@ impl PgBank {
             TransactionError::InvalidAccountForFee
         })? {
             SystemAccountKind::System => 0,
-            SystemAccountKind::Nonce => todo!(),
+            SystemAccountKind::Nonce => rent.minimum_balance(nonce::State::size()),
         };
 
-        if payer_account.lamports() < fee + min_balance {
+        if payer_account.lamports() < fee.saturating_add(min_balance) {
             error_counters.insufficient_funds += 1;
             return Err(TransactionError::InsufficientFundsForFee);
         }
 
-        Ok(())
+        payer_account
+            .checked_sub_lamports(fee)
+            .map_err(|_| TransactionError::InsufficientFundsForFee)
     }
 
     fn accumulate_and_check_loaded_account_data_size(
@@ -875,17 +1914,24 @@ pub type BankAccounts = HashMap<Pubkey, Account>;
 
 /// Mapping between Signatures and TransactionData
 pub type BankTxs = HashMap<Signature, TransactionData>;
+pub type BankBlocks = BTreeMap<Slot, Block>;
 
-/// Filler struct, address loader is not yet implemented
-#[derive(Clone, Default)]
-pub struct PgAddressLoader {}
+/// Loads the addresses of the v0 messages from the lookup tables in the bank
+#[derive(Clone, Copy)]
+pub struct PgAddressLoader<'a> {
+    bank: &'a PgBank,
+}
 
-impl AddressLoader for PgAddressLoader {
+impl AddressLoader for PgAddressLoader<'_> {
     fn load_addresses(
         self,
-        _lookups: &[MessageAddressTableLookup],
+        lookups: &[MessageAddressTableLookup],
     ) -> Result<LoadedAddresses, AddressLoaderError> {
-        Err(AddressLoaderError::Disabled)
+        lookups
+            .iter()
+            .map(|lookup| self.bank.load_lookup_table_addresses(lookup))
+            .collect::<Result<_, _>>()
+            .map_err(AddressLoaderError::from)
     }
 }
 
@@ -927,6 +1973,8 @@ pub struct TransactionExecutionDetails {
     /// The change in accounts data len for this transaction.
     /// NOTE: This value is valid if `status` is `Ok`.
     pub accounts_data_len_delta: i64,
+    /// Execution details of the programs of the transaction instructions
+    pub program_details: Vec<ProgramExecutionDetails>,
 }
 
 #[allow(dead_code)]
@@ -952,6 +2000,99 @@ pub struct InnerInstruction {
     pub stack_height: u8,
 }
 
+/// Extracts the cross program invocations of each instruction from the trace
+fn inner_instructions_list_from_instruction_trace(
+    instruction_trace: &InstructionTrace,
+) -> InnerInstructionsList {
+    instruction_trace
+        .iter()
+        .map(|inner_instructions_trace| {
+            // The first instruction is the transaction instruction itself
+            inner_instructions_trace
+                .iter()
+                .skip(1)
+                .map(|instruction_context| InnerInstruction {
+                    instruction: CompiledInstruction::new_from_raw_parts(
+                        instruction_context
+                            .get_index_of_program_account_in_transaction(
+                                instruction_context
+                                    .get_number_of_program_accounts()
+                                    .saturating_sub(1),
+                            )
+                            .unwrap_or_default() as u8,
+                        instruction_context.get_instruction_data().to_vec(),
+                        (0..instruction_context.get_number_of_instruction_accounts())
+                            .map(|instruction_account_index| {
+                                instruction_context
+                                    .get_index_of_instruction_account_in_transaction(
+                                        instruction_account_index,
+                                    )
+                                    .unwrap_or_default() as u8
+                            })
+                            .collect(),
+                    ),
+                    stack_height: instruction_context.get_stack_height() as u8,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Converts the inner instructions to the ledger format, the instructions
+/// without cross program invocations are left out
+fn compile_inner_instructions(
+    inner_instructions_list: InnerInstructionsList,
+) -> Vec<CompiledInnerInstruction> {
+    inner_instructions_list
+        .into_iter()
+        .enumerate()
+        .filter(|(_, inner_instructions)| !inner_instructions.is_empty())
+        .map(|(index, inner_instructions)| CompiledInnerInstruction {
+            index: index as u8,
+            instructions: inner_instructions
+                .into_iter()
+                .map(|inner_instruction| inner_instruction.instruction)
+                .collect(),
+        })
+        .collect()
+}
+
+/// Collects the execution details of the programs of the transaction
+/// instructions in the order they were first invoked. The timings are summed
+/// from the invocations since the runtime only tracks the units per program.
+fn program_execution_details(
+    timings: &ExecuteTimings,
+    invocations: &[Invocation],
+) -> Vec<ProgramExecutionDetails> {
+    let mut program_details: Vec<ProgramExecutionDetails> = vec![];
+    for invocation in invocations {
+        match program_details
+            .iter_mut()
+            .find(|details| details.program_id == invocation.program_id)
+        {
+            Some(details) => details.timings = details.timings.saturating_add(&invocation.timings),
+            // Precompiles are verified instead of getting executed
+            None => {
+                if let Some(program_timing) = timings
+                    .details
+                    .per_program_timings
+                    .get(&invocation.program_id)
+                {
+                    program_details.push(ProgramExecutionDetails {
+                        program_id: invocation.program_id,
+                        count: program_timing.count,
+                        units_consumed: program_timing.accumulated_units,
+                        errored_units: program_timing.total_errored_units,
+                        timings: invocation.timings,
+                    });
+                }
+            }
+        }
+    }
+
+    program_details
+}
+
 #[derive(Default)]
 pub struct TransactionErrorMetrics {
     pub total: usize,
@@ -978,3 +2119,71 @@ pub struct TransactionErrorMetrics {
     pub max_loaded_accounts_data_size_exceeded: usize,
     pub invalid_loaded_accounts_data_size_limit: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Signer;
+
+    use super::*;
+
+    /// Every bank adds 4 native program and 5 sysvar accounts with 1 lamport
+    const BANK_ACCOUNT_LAMPORTS: u64 = 9;
+
+    fn create_genesis_config(faucet: &Pubkey) -> GenesisConfig {
+        GenesisConfig::new(
+            &[
+                (
+                    *faucet,
+                    AccountSharedData::new(1_000_000, 0, &system_program::id()),
+                ),
+                (
+                    Pubkey::new_unique(),
+                    AccountSharedData::new(500, 0, &system_program::id()),
+                ),
+            ],
+            &[],
+        )
+    }
+
+    #[test]
+    fn genesis_funds_the_faucet() {
+        let faucet = Keypair::new();
+        let genesis_config = create_genesis_config(&faucet.pubkey());
+        let mut bank = PgBank::from_genesis_config(&genesis_config, Some(faucet));
+
+        assert_eq!(
+            bank.get_capitalization(),
+            1_000_500 + BANK_ACCOUNT_LAMPORTS
+        );
+        let to = Pubkey::new_unique();
+        bank.request_airdrop(&to, 1000).unwrap();
+        assert_eq!(bank.get_account(&to).unwrap().lamports, 1000);
+        // Only the fee of the airdrop got burned
+        assert_eq!(
+            bank.get_capitalization(),
+            1_000_500 + BANK_ACCOUNT_LAMPORTS - bank.get_lamports_per_signature()
+        );
+    }
+
+    #[test]
+    fn genesis_without_faucet_keypair() {
+        let genesis_config = create_genesis_config(&Pubkey::new_unique());
+        let mut bank = PgBank::from_genesis_config(&genesis_config, None);
+
+        assert_eq!(
+            bank.get_capitalization(),
+            1_000_500 + BANK_ACCOUNT_LAMPORTS
+        );
+        assert!(matches!(
+            bank.request_airdrop(&Pubkey::new_unique(), 1000),
+            Err(error::PlaynetError::FaucetExhausted { .. })
+        ));
+
+        // The faucet doesn't get funded when the bank gets read back either
+        let bank = PgBank::new(Some(serde_json::to_string(&bank).unwrap())).unwrap();
+        assert_eq!(
+            bank.get_capitalization(),
+            1_000_500 + BANK_ACCOUNT_LAMPORTS
+        );
+    }
+}
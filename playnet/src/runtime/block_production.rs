@@ -0,0 +1,64 @@
+// Slots end either after a number of transactions, on a timer or only when
+// they're explicitly advanced. Every mode ends the slots through the same path,
+// so the blockhash, blocks and sysvars advance the same way regardless of the
+// mode.
+//
+// The bank can't run timers itself, the host calls `advance_slots` on every
+// tick of the interval mode. The `Clock` sysvar always advances by the slot
+// duration of the bank so that time based program logic stays deterministic.
+
+use std::{str::FromStr, time::Duration};
+
+use crate::error::PlaynetError;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockProduction {
+    /// A slot ends after every transaction
+    #[default]
+    PerTransaction,
+    /// A slot ends after the given number of transactions
+    Batch(usize),
+    /// A slot ends every interval, regardless of the transactions
+    Interval(Duration),
+    /// Slots only end when they're advanced manually
+    Manual,
+}
+
+impl BlockProduction {
+    /// Returns whether a slot with `tx_count` transactions ends
+    pub fn ends_slot(&self, tx_count: usize) -> bool {
+        match self {
+            Self::PerTransaction => true,
+            Self::Batch(size) => tx_count >= *size,
+            Self::Interval(_) | Self::Manual => false,
+        }
+    }
+}
+
+impl FromStr for BlockProduction {
+    type Err = PlaynetError;
+
+    /// Parses `per-transaction`, `batch:<transactions>`, `interval:<ms>` or
+    /// `manual`
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            PlaynetError::InvalidParam(format!(
+                "unsupported block production: {}. Supported modes: per-transaction, \
+                batch:<transactions>, interval:<ms>, manual",
+                mode
+            ))
+        };
+
+        let (name, value) = match mode.split_once(':') {
+            Some((name, value)) => (name, Some(value.parse::<u64>().map_err(|_| invalid())?)),
+            None => (mode, None),
+        };
+        match (name, value) {
+            ("per-transaction", None) => Ok(Self::PerTransaction),
+            ("batch", Some(size)) if size > 0 => Ok(Self::Batch(size as usize)),
+            ("interval", Some(ms)) if ms > 0 => Ok(Self::Interval(Duration::from_millis(ms))),
+            ("manual", None) => Ok(Self::Manual),
+            _ => Err(invalid()),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{clock::UnixTimestamp, hash::Hash, signature::Signature, slot_history::Slot};
+
+/// Record of a produced block
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Block {
+    /// The slot of the parent block
+    pub parent_slot: Slot,
+    /// The blockhash of the block
+    pub blockhash: Hash,
+    /// The blockhash of the parent block
+    pub previous_blockhash: Hash,
+    /// The unix timestamp of when the block was produced
+    pub block_time: Option<UnixTimestamp>,
+    /// The number of blocks beneath the block
+    pub block_height: u64,
+    /// Signatures of the transactions in the block in processing order
+    pub signatures: Vec<Signature>,
+}
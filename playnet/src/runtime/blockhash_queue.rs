@@ -0,0 +1,71 @@
+// Recent blockhashes of the bank.
+//
+// Transactions can only use the blockhashes that are at most
+// `MAX_PROCESSING_AGE` slots old. The queue also backs the `RecentBlockhashes`
+// and `SlotHashes` sysvars, so it keeps as many blockhashes as `SlotHashes`
+// can hold.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+#[allow(deprecated)]
+use solana_sdk::sysvar::recent_blockhashes::{self, IterItem, RecentBlockhashes};
+use solana_sdk::{
+    clock::MAX_PROCESSING_AGE,
+    hash::Hash,
+    slot_hashes::{self, SlotHashes},
+    slot_history::Slot,
+};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BlockhashQueue {
+    /// Blockhashes with the slot they became the latest blockhash in, newest
+    /// first
+    entries: VecDeque<(Slot, Hash)>,
+}
+
+impl BlockhashQueue {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Registers the blockhash that becomes the latest blockhash in `slot`
+    pub fn register(&mut self, slot: Slot, hash: Hash) {
+        self.entries.push_front((slot, hash));
+        self.entries.truncate(slot_hashes::MAX_ENTRIES);
+    }
+
+    /// Number of blockhashes registered after the blockhash, `None` if the
+    /// blockhash is not in the queue
+    pub fn get_age(&self, hash: &Hash) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(_, entry_hash)| entry_hash == hash)
+    }
+
+    /// Whether transactions can use the blockhash
+    pub fn is_valid(&self, hash: &Hash) -> bool {
+        self.get_age(hash)
+            .map(|age| age <= MAX_PROCESSING_AGE)
+            .unwrap_or(false)
+    }
+
+    /// Hashes of the finished slots, the blockhash that became the latest in
+    /// a slot is the hash of the previous slot
+    pub fn slot_hashes(&self) -> SlotHashes {
+        self.entries
+            .iter()
+            .filter(|(slot, _)| *slot != 0)
+            .map(|(slot, hash)| (slot - 1, *hash))
+            .collect()
+    }
+
+    #[allow(deprecated)]
+    pub fn recent_blockhashes(&self, lamports_per_signature: u64) -> RecentBlockhashes {
+        self.entries
+            .iter()
+            .take(recent_blockhashes::MAX_ENTRIES)
+            .map(|(slot, hash)| IterItem(*slot, hash, lamports_per_signature))
+            .collect()
+    }
+}
@@ -0,0 +1,659 @@
+// BPF programs are executed by the executors that the BPF loader creates and
+// caches in the transaction. The loader only enables the instruction tracer of
+// the VM when the `log` crate is at the trace level, so when tracing is enabled
+// an executor with the tracer enabled is added to the transaction before the
+// loader gets to create its own. The executor is the same as the one of
+// `solana-bpf-loader-program` 1.14.6, except that it hands the trace of every
+// invocation to the invocation recorder and that the loader stages aren't
+// timed.
+//
+// Traces are rendered as disassembly with the function symbols of the ELF,
+// native hosts can have them written to a file per invocation.
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use solana_bpf_loader_program::{
+    create_vm,
+    serialization::{deserialize_parameters, serialize_parameters},
+    syscalls::{register_syscalls, SyscallError},
+    BpfError, ThisInstructionMeter,
+};
+use solana_program_runtime::{
+    compute_budget::ComputeBudget, executor_cache::Executor, ic_logger_msg,
+    invoke_context::InvokeContext, stable_log,
+};
+use solana_rbpf::{
+    disassembler::disassemble_instruction,
+    ebpf,
+    elf::Executable,
+    error::EbpfError,
+    static_analysis::Analysis,
+    verifier::RequisiteVerifier,
+    vm::{Config, VerifiedExecutable},
+};
+use solana_sdk::{
+    bpf_loader, bpf_loader_deprecated,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    entrypoint::SUCCESS,
+    feature_set::{
+        cap_accounts_data_len, cap_bpf_program_instruction_accounts,
+        disable_bpf_unresolved_symbols_at_runtime, error_on_syscall_bpf_function_hash_collisions,
+        reject_callx_r10, FeatureSet,
+    },
+    instruction::InstructionError,
+    program_error::MAX_ACCOUNTS_DATA_SIZE_EXCEEDED,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction_context::{BorrowedAccount, InstructionContext, TransactionContext},
+};
+use tracing::debug;
+
+use super::invocation::{record_trace, Invocation};
+
+type BpfExecutable = VerifiedExecutable<RequisiteVerifier, BpfError, ThisInstructionMeter>;
+
+/// Whether the instructions that BPF programs execute get traced
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum BpfTracing {
+    #[default]
+    Disabled,
+    /// The traces are kept in the invocations
+    Enabled,
+    /// The traces are also written to the directory, a file per invocation
+    Directory(PathBuf),
+}
+
+impl BpfTracing {
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Disabled)
+    }
+}
+
+/// Instructions executed by a BPF invocation
+#[derive(Clone)]
+pub struct BpfTrace {
+    executable: Arc<BpfExecutable>,
+    /// Registers `r0` to `r10` and the program counter before every executed
+    /// instruction
+    entries: Vec<[u64; 12]>,
+}
+
+impl BpfTrace {
+    pub fn entries(&self) -> &[[u64; 12]] {
+        &self.entries
+    }
+
+    /// Renders the executed instructions as disassembly, the instructions at
+    /// the start of the functions are labeled with their ELF symbols
+    pub fn disassemble(&self) -> String {
+        let mut output = vec![];
+        self.write_disassembly(&mut output)
+            .expect("Writing to a `Vec` can't fail");
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    /// Same as `disassemble` but writes the disassembly to the writer as it
+    /// gets rendered
+    pub fn write_disassembly<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let analysis = match Analysis::from_executable(self.executable.get_executable()) {
+            Ok(analysis) => analysis,
+            Err(err) => return writeln!(writer, "Failed to analyze the program: {}", err),
+        };
+
+        // `lddw` takes two slots
+        let mut pc_to_insn_index = vec![
+            0usize;
+            analysis
+                .instructions
+                .last()
+                .map(|insn| insn.ptr + 2)
+                .unwrap_or(0)
+        ];
+        for (index, insn) in analysis.instructions.iter().enumerate() {
+            pc_to_insn_index[insn.ptr] = index;
+            pc_to_insn_index[insn.ptr + 1] = index;
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let pc = entry[11] as usize;
+            if let Some((_, name)) = analysis.functions.get(&pc) {
+                writeln!(writer, "{}:", name)?;
+            }
+            let disassembly = pc_to_insn_index
+                .get(pc)
+                .and_then(|insn_index| analysis.instructions.get(*insn_index))
+                .map(|insn| disassemble_instruction(insn, &analysis))
+                .unwrap_or_else(|| "<invalid pc>".into());
+            writeln!(
+                writer,
+                "{:5} {:016X?} {:5}: {}",
+                index,
+                &entry[0..11],
+                pc + ebpf::ELF_INSN_DUMP_OFFSET,
+                disassembly,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BpfTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BpfTrace")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl PartialEq for BpfTrace {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.executable, &other.executable) && self.entries == other.entries
+    }
+}
+
+impl Eq for BpfTrace {}
+
+/// Writes the trace of every traced invocation of the transaction to
+/// `<dir>/<signature>/<position>-<program id>.trace`, where the position is
+/// the index path of the invocation, e.g. `2.1` for the first invocation made
+/// by the second instruction. The directory of the transaction is only created
+/// if any of its invocations were traced.
+pub fn write_traces(
+    dir: &Path,
+    signature: &Signature,
+    invocations: &[Invocation],
+) -> io::Result<()> {
+    write_invocation_traces(&dir.join(signature.to_string()), "", invocations)
+}
+
+fn write_invocation_traces(dir: &Path, prefix: &str, invocations: &[Invocation]) -> io::Result<()> {
+    for (index, invocation) in invocations.iter().enumerate() {
+        let position = format!("{}{}", prefix, index + 1);
+        if let Some(trace) = &invocation.trace {
+            let result = match &invocation.result {
+                Ok(()) => "success".into(),
+                Err(err) => format!("failed: {}", err),
+            };
+            fs::create_dir_all(dir)?;
+            let path = dir.join(format!("{}-{}.trace", position, invocation.program_id));
+            let mut writer = BufWriter::new(File::create(path)?);
+            write!(
+                writer,
+                "Program {} invoke [{}]\nProgram {} {}\n\n",
+                invocation.program_id, invocation.depth, invocation.program_id, result,
+            )?;
+            trace.write_disassembly(&mut writer)?;
+            writer.flush()?;
+        }
+        write_invocation_traces(
+            dir,
+            &format!("{}.", position),
+            &invocation.inner_invocations,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Adds a tracing executor for the BPF program of the current instruction
+/// unless the transaction already has an executor for the program. The loader
+/// reports the programs that can't be loaded.
+pub(super) fn add_tracing_executor(
+    first_instruction_account: usize,
+    invoke_context: &mut InvokeContext,
+) {
+    let (program_id, elf) = match get_program_elf(first_instruction_account, invoke_context) {
+        Ok(Some(program)) => program,
+        _ => return,
+    };
+    if invoke_context.get_executor(&program_id).is_some() {
+        return;
+    }
+
+    match create_executable(&elf, invoke_context) {
+        Ok(executable) => invoke_context.add_executor(
+            &program_id,
+            Arc::new(TracingExecutor {
+                executable: Arc::new(executable),
+            }),
+        ),
+        Err(err) => debug!(%program_id, %err, "Failed to create a tracing executor"),
+    }
+}
+
+/// Returns the program id and the ELF if the instruction executes a BPF program.
+/// The program account is found the same way as `process_instruction_common`
+/// of `solana-bpf-loader-program` 1.14.6 finds it: it's the first instruction
+/// account or the account after it, after the program data of upgradeable
+/// programs. Otherwise the instruction is a loader instruction.
+fn get_program_elf(
+    first_instruction_account: usize,
+    invoke_context: &InvokeContext,
+) -> Result<Option<(Pubkey, Vec<u8>)>, InstructionError> {
+    let transaction_context = &invoke_context.transaction_context;
+    let instruction_context = transaction_context.get_current_instruction_context()?;
+    let program_id = *instruction_context.get_last_program_key(transaction_context)?;
+    let first_account_key = transaction_context.get_key_of_account_at_index(
+        get_index_in_transaction(instruction_context, first_instruction_account)?,
+    )?;
+    let second_account_key = get_index_in_transaction(
+        instruction_context,
+        first_instruction_account.saturating_add(1),
+    )
+    .and_then(|index_in_transaction| {
+        transaction_context.get_key_of_account_at_index(index_in_transaction)
+    });
+
+    let program_account_index = if *first_account_key == program_id {
+        first_instruction_account
+    } else if second_account_key
+        .map(|key| *key == program_id)
+        .unwrap_or(false)
+    {
+        first_instruction_account.saturating_add(1)
+    } else {
+        return Ok(None);
+    };
+
+    let program = try_borrow_account(
+        transaction_context,
+        instruction_context,
+        program_account_index,
+    )?;
+    if !program.is_executable() {
+        return Ok(None);
+    }
+    let offset = if bpf_loader_upgradeable::check_id(program.get_owner()) {
+        match program.get_state()? {
+            UpgradeableLoaderState::Program {
+                programdata_address,
+            } if programdata_address == *first_account_key => {}
+            _ => return Ok(None),
+        }
+        UpgradeableLoaderState::size_of_programdata_metadata()
+    } else if bpf_loader::check_id(program.get_owner())
+        || bpf_loader_deprecated::check_id(program.get_owner())
+    {
+        0
+    } else {
+        return Ok(None);
+    };
+    drop(program);
+
+    // The ELF is in the program data of upgradeable programs, which is the
+    // first instruction account, and in the program account otherwise
+    let programdata = try_borrow_account(
+        transaction_context,
+        instruction_context,
+        first_instruction_account,
+    )?;
+    if offset != 0
+        && !matches!(
+            programdata.get_state()?,
+            UpgradeableLoaderState::ProgramData { .. }
+        )
+    {
+        return Ok(None);
+    }
+
+    Ok(programdata
+        .get_data()
+        .get(offset..)
+        .map(|elf| (program_id, elf.to_vec())))
+}
+
+/// Index in the transaction of the account at the index in the instruction,
+/// counting the program accounts first like the loader does
+fn get_index_in_transaction(
+    instruction_context: &InstructionContext,
+    index_in_instruction: usize,
+) -> Result<usize, InstructionError> {
+    let program_accounts = instruction_context.get_number_of_program_accounts();
+    if index_in_instruction < program_accounts {
+        instruction_context.get_index_of_program_account_in_transaction(index_in_instruction)
+    } else {
+        instruction_context.get_index_of_instruction_account_in_transaction(
+            index_in_instruction - program_accounts,
+        )
+    }
+}
+
+fn try_borrow_account<'a>(
+    transaction_context: &'a TransactionContext,
+    instruction_context: &'a InstructionContext,
+    index_in_instruction: usize,
+) -> Result<BorrowedAccount<'a>, InstructionError> {
+    let program_accounts = instruction_context.get_number_of_program_accounts();
+    if index_in_instruction < program_accounts {
+        instruction_context.try_borrow_program_account(transaction_context, index_in_instruction)
+    } else {
+        instruction_context.try_borrow_instruction_account(
+            transaction_context,
+            index_in_instruction - program_accounts,
+        )
+    }
+}
+
+/// Same as the executable of the loader but with the tracer and the symbols
+fn create_executable(
+    elf: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<BpfExecutable, EbpfError<BpfError>> {
+    let syscall_registry = register_syscalls(invoke_context, false)?;
+    let config = executable_config(
+        invoke_context.get_compute_budget(),
+        &invoke_context.feature_set,
+        true,
+    );
+    let executable = Executable::from_elf(elf, config, syscall_registry)?;
+    BpfExecutable::from_executable(executable)
+}
+
+/// Mirrors the `Config` that `create_executor` of `solana-bpf-loader-program`
+/// 1.14.6 uses for the programs that get invoked rather than deployed, it has
+/// to be updated together with the loader. Tracing only changes the
+/// instruction tracing and the symbols, neither of which affects the compute
+/// units.
+fn executable_config(
+    compute_budget: &ComputeBudget,
+    feature_set: &FeatureSet,
+    tracing: bool,
+) -> Config {
+    Config {
+        max_call_depth: compute_budget.max_call_depth,
+        stack_frame_size: compute_budget.stack_frame_size,
+        enable_stack_frame_gaps: true,
+        instruction_meter_checkpoint_distance: 10000,
+        enable_instruction_meter: true,
+        enable_instruction_tracing: tracing,
+        enable_symbol_and_section_labels: tracing,
+        disable_unresolved_symbols_at_runtime: feature_set
+            .is_active(&disable_bpf_unresolved_symbols_at_runtime::id()),
+        reject_broken_elfs: false,
+        noop_instruction_rate: 256,
+        sanitize_user_provided_values: true,
+        encrypt_environment_registers: true,
+        disable_deprecated_load_instructions: false,
+        syscall_bpf_function_hash_collision: feature_set
+            .is_active(&error_on_syscall_bpf_function_hash_collisions::id()),
+        reject_callx_r10: feature_set.is_active(&reject_callx_r10::id()),
+        dynamic_stack_frames: false,
+        enable_sdiv: false,
+        optimize_rodata: false,
+        static_syscalls: false,
+        enable_elf_vaddr: false,
+    }
+}
+
+struct TracingExecutor {
+    executable: Arc<BpfExecutable>,
+}
+
+impl fmt::Debug for TracingExecutor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TracingExecutor({:p})", self)
+    }
+}
+
+impl Executor for TracingExecutor {
+    fn execute(
+        &self,
+        _first_instruction_account: usize,
+        invoke_context: &mut InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let log_collector = invoke_context.get_log_collector();
+        let compute_meter = invoke_context.get_compute_meter();
+        let stack_height = invoke_context.get_stack_height();
+        let transaction_context = &invoke_context.transaction_context;
+        let instruction_context = transaction_context.get_current_instruction_context()?;
+        let program_id = *instruction_context.get_last_program_key(transaction_context)?;
+
+        let (mut parameter_bytes, account_lengths) = serialize_parameters(
+            invoke_context.transaction_context,
+            instruction_context,
+            invoke_context
+                .feature_set
+                .is_active(&cap_bpf_program_instruction_accounts::ID),
+        )?;
+
+        let execution_result = {
+            let mut vm = match create_vm(
+                &self.executable,
+                parameter_bytes.as_slice_mut(),
+                account_lengths,
+                invoke_context,
+            ) {
+                Ok(info) => info,
+                Err(e) => {
+                    ic_logger_msg!(log_collector, "Failed to create BPF VM: {}", e);
+                    return Err(InstructionError::ProgramEnvironmentSetupFailure);
+                }
+            };
+
+            stable_log::program_invoke(&log_collector, &program_id, stack_height);
+            let mut instruction_meter = ThisInstructionMeter {
+                compute_meter: compute_meter.clone(),
+            };
+            let before = compute_meter.borrow().get_remaining();
+            let result = vm.execute_program_interpreted(&mut instruction_meter);
+            let after = compute_meter.borrow().get_remaining();
+            ic_logger_msg!(
+                log_collector,
+                "Program {} consumed {} of {} compute units",
+                &program_id,
+                before.saturating_sub(after),
+                before
+            );
+            record_trace(BpfTrace {
+                executable: Arc::clone(&self.executable),
+                entries: vm.get_tracer().log.clone(),
+            });
+            drop(vm);
+            let (_returned_from_program_id, return_data) =
+                invoke_context.transaction_context.get_return_data();
+            if !return_data.is_empty() {
+                stable_log::program_return(&log_collector, &program_id, return_data);
+            }
+            match result {
+                Ok(status) if status != SUCCESS => {
+                    let error: InstructionError = if status == MAX_ACCOUNTS_DATA_SIZE_EXCEEDED
+                        && !invoke_context
+                            .feature_set
+                            .is_active(&cap_accounts_data_len::id())
+                    {
+                        InstructionError::InvalidError
+                    } else {
+                        status.into()
+                    };
+                    stable_log::program_failure(&log_collector, &program_id, &error);
+                    Err(error)
+                }
+                Err(error) => {
+                    let error = match error {
+                        EbpfError::UserError(BpfError::SyscallError(
+                            SyscallError::InstructionError(error),
+                        )) => error,
+                        err => {
+                            ic_logger_msg!(log_collector, "Program failed to complete: {}", err);
+                            InstructionError::ProgramFailedToComplete
+                        }
+                    };
+                    stable_log::program_failure(&log_collector, &program_id, &error);
+                    Err(error)
+                }
+                _ => Ok(()),
+            }
+        };
+
+        let execute_or_deserialize_result = execution_result.and_then(|_| {
+            deserialize_parameters(
+                invoke_context.transaction_context,
+                invoke_context
+                    .transaction_context
+                    .get_current_instruction_context()?,
+                parameter_bytes.as_slice(),
+                invoke_context.get_orig_account_lengths()?,
+            )
+        });
+
+        if execute_or_deserialize_result.is_ok() {
+            stable_log::program_success(&log_collector, &program_id);
+        }
+        execute_or_deserialize_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program_runtime::invoke_context::ComputeMeter;
+    use solana_rbpf::vm::{EbpfVm, SyscallRegistry};
+    use solana_sdk::{
+        account::Account,
+        instruction::Instruction,
+        signature::{Keypair, Signer},
+        transaction::{SanitizedTransaction, Transaction},
+    };
+
+    use crate::runtime::{bank::PgBank, block_production::BlockProduction};
+
+    use super::*;
+
+    const ELF: &[u8] = include_bytes!("../../test_elfs/pass_stack_reference.so");
+
+    /// Executes the program without any input and returns its result, the
+    /// consumed compute units and the number of traced instructions
+    fn execute(elf: &[u8], tracing: bool) -> (u64, u64, usize) {
+        let config = executable_config(
+            &ComputeBudget::default(),
+            &FeatureSet::all_enabled(),
+            tracing,
+        );
+        let executable = Executable::from_elf(elf, config, SyscallRegistry::default()).unwrap();
+        let executable = BpfExecutable::from_executable(executable).unwrap();
+        let mut vm = EbpfVm::new(&executable, &mut [], vec![]).unwrap();
+
+        let compute_units = ComputeBudget::default().compute_unit_limit;
+        let compute_meter = ComputeMeter::new_ref(compute_units);
+        let mut instruction_meter = ThisInstructionMeter {
+            compute_meter: compute_meter.clone(),
+        };
+        let result = vm
+            .execute_program_interpreted(&mut instruction_meter)
+            .unwrap();
+        let remaining = compute_meter.borrow().get_remaining();
+
+        (result, compute_units - remaining, vm.get_tracer().log.len())
+    }
+
+    #[test]
+    fn tracing_consumes_the_same_compute_units() {
+        let (untraced_result, untraced_units, untraced_len) = execute(ELF, false);
+        let (traced_result, traced_units, traced_len) = execute(ELF, true);
+
+        assert_eq!(untraced_result, 42);
+        assert_eq!(traced_result, untraced_result);
+        assert_eq!(traced_units, untraced_units);
+        assert_eq!(untraced_len, 0);
+        assert_eq!(traced_len as u64, traced_units);
+    }
+
+    /// Creates a bank with BPF tracing enabled and a funded payer
+    fn create_bank() -> (PgBank, Keypair) {
+        let mut bank = PgBank::new(None).unwrap();
+        bank.set_block_production(BlockProduction::Manual);
+        bank.set_bpf_tracing(BpfTracing::Enabled);
+        let payer = Keypair::new();
+        bank.request_airdrop(&payer.pubkey(), 1_000_000_000)
+            .unwrap();
+        (bank, payer)
+    }
+
+    fn create_tx(bank: &PgBank, payer: &Keypair, program_id: Pubkey) -> SanitizedTransaction {
+        let tx = Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bytes(program_id, &[], vec![])],
+            Some(&payer.pubkey()),
+            &[payer],
+            bank.get_latest_blockhash(),
+        );
+        SanitizedTransaction::from_transaction_for_tests(tx)
+    }
+
+    fn assert_traced(bank: &PgBank, tx: &SanitizedTransaction, program_id: &Pubkey) {
+        let result = bank.simulate_tx(tx);
+        let invocation = &result.invocations[0];
+        assert_eq!(invocation.program_id, *program_id);
+        assert_eq!(invocation.depth, 1);
+        let trace = invocation
+            .trace
+            .as_ref()
+            .expect("Top-level invocation wasn't traced");
+        assert!(!trace.entries().is_empty());
+    }
+
+    #[test]
+    fn trace_bpf_loader_program() {
+        let (mut bank, payer) = create_bank();
+        let program_id = Pubkey::new_unique();
+        bank.set_account(
+            program_id,
+            Account {
+                lamports: bank.get_minimum_balance_for_rent_exemption(ELF.len()),
+                data: ELF.to_vec(),
+                owner: bpf_loader::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+
+        let tx = create_tx(&bank, &payer, program_id);
+        assert_traced(&bank, &tx, &program_id);
+    }
+
+    #[test]
+    fn trace_upgradeable_program() {
+        let (mut bank, payer) = create_bank();
+        let program_id = Pubkey::new_unique();
+        let programdata_address = Pubkey::new_unique();
+        let program_data = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address,
+        })
+        .unwrap();
+        bank.set_account(
+            program_id,
+            Account {
+                lamports: bank.get_minimum_balance_for_rent_exemption(program_data.len()),
+                data: program_data,
+                owner: bpf_loader_upgradeable::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+        let mut programdata = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: None,
+        })
+        .unwrap();
+        programdata.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        programdata.extend_from_slice(ELF);
+        bank.set_account(
+            programdata_address,
+            Account {
+                lamports: bank.get_minimum_balance_for_rent_exemption(programdata.len()),
+                data: programdata,
+                owner: bpf_loader_upgradeable::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let tx = create_tx(&bank, &payer, program_id);
+        assert_traced(&bank, &tx, &program_id);
+    }
+}
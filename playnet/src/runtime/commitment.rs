@@ -0,0 +1,169 @@
+// Playnet doesn't have validators voting on its blocks, so the commitment levels
+// are simulated: a slot gets confirmed and finalized once enough slots are built
+// on top of it and, optionally, once enough time has passed since it was
+// processed.
+//
+// Committed account changes are kept until their slot gets finalized so that
+// the accounts can be read at the confirmed and finalized slots.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use solana_sdk::{
+    account::Account, clock::MAX_RECENT_BLOCKHASHES, pubkey::Pubkey, slot_history::Slot,
+};
+
+use crate::types::CommitmentLevel;
+
+/// Number of slots the status cache keeps the transaction statuses for, older
+/// transactions are only found when searching the transaction history
+pub const STATUS_CACHE_SLOTS: Slot = MAX_RECENT_BLOCKHASHES as Slot;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CommitmentConfig {
+    /// Number of slots that need to be built on top of a slot to confirm it
+    pub confirmation_depth: u64,
+    /// Number of slots that need to be built on top of a slot to finalize it
+    pub finalization_depth: u64,
+    /// Minimum time a slot spends at each commitment level, i.e. slots get
+    /// confirmed `latency` after they're processed and finalized `latency`
+    /// after they're confirmed at the earliest
+    pub latency: Option<Duration>,
+}
+
+impl Default for CommitmentConfig {
+    /// Same depths as a cluster with the 32 slot vote lockout
+    fn default() -> Self {
+        Self {
+            confirmation_depth: 1,
+            finalization_depth: 32,
+            latency: None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CommitmentTracker {
+    config: CommitmentConfig,
+    /// When the slots that are still waiting on the latency were processed
+    slot_times: BTreeMap<Slot, Duration>,
+    /// The states of the accounts before their first change in each slot that
+    /// is not finalized yet, `None` if the account didn't exist
+    account_history: BTreeMap<Slot, HashMap<Pubkey, Option<Account>>>,
+}
+
+impl CommitmentTracker {
+    pub fn config(&self) -> CommitmentConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: CommitmentConfig) {
+        self.config = config;
+    }
+
+    /// Records the state of the account before it changed in `slot`
+    pub fn record_account_change(
+        &mut self,
+        slot: Slot,
+        pubkey: Pubkey,
+        previous: &Option<Account>,
+    ) {
+        self.account_history
+            .entry(slot)
+            .or_default()
+            .entry(pubkey)
+            .or_insert_with(|| previous.clone());
+    }
+
+    /// Starts tracking the finished slot and forgets the history of the
+    /// finalized slots
+    pub fn on_slot_finished(&mut self, slot: Slot) {
+        if let Some(latency) = self.config.latency {
+            let now = now();
+            self.slot_times.insert(slot, now);
+            self.slot_times
+                .retain(|_, time| now.saturating_sub(*time) < latency.saturating_mul(2));
+        } else {
+            self.slot_times.clear();
+        }
+
+        if let Some(finalized_slot) = self.get_commitment_slot(CommitmentLevel::Finalized, slot + 1)
+        {
+            self.account_history = self.account_history.split_off(&(finalized_slot + 1));
+        }
+    }
+
+    /// Returns the highest slot that reached the commitment level, `None` if
+    /// no slot has reached it yet
+    pub fn get_commitment_slot(
+        &self,
+        commitment: CommitmentLevel,
+        current_slot: Slot,
+    ) -> Option<Slot> {
+        let (depth, stages) = match commitment {
+            CommitmentLevel::Processed => return Some(current_slot),
+            CommitmentLevel::Confirmed => (self.config.confirmation_depth, 1),
+            CommitmentLevel::Finalized => (self.config.finalization_depth, 2),
+        };
+        let slot = current_slot.checked_sub(depth)?;
+
+        match self.config.latency {
+            Some(latency) => {
+                // Slots are processed in order, so the slots after the first
+                // slot that is still waiting are waiting too
+                let now = now();
+                let wait = latency.saturating_mul(stages);
+                match self
+                    .slot_times
+                    .range(..=slot)
+                    .find(|(_, time)| now.saturating_sub(**time) < wait)
+                {
+                    Some((waiting_slot, _)) => waiting_slot.checked_sub(1),
+                    None => Some(slot),
+                }
+            }
+            None => Some(slot),
+        }
+    }
+
+    /// Returns the commitment level the slot has reached
+    pub fn get_commitment_level(&self, slot: Slot, current_slot: Slot) -> CommitmentLevel {
+        let reached = |commitment| {
+            self.get_commitment_slot(commitment, current_slot)
+                .map(|commitment_slot| slot <= commitment_slot)
+                .unwrap_or(false)
+        };
+
+        if reached(CommitmentLevel::Finalized) {
+            CommitmentLevel::Finalized
+        } else if reached(CommitmentLevel::Confirmed) {
+            CommitmentLevel::Confirmed
+        } else {
+            CommitmentLevel::Processed
+        }
+    }
+
+    /// Returns the state of the account at the end of `slot`, or before the
+    /// first slot if `slot` is `None`. Returns `None` if the account hasn't
+    /// changed since then and its current state applies.
+    pub fn get_account_at(&self, pubkey: &Pubkey, slot: Option<Slot>) -> Option<Option<&Account>> {
+        self.account_history
+            .range(slot.map(|slot| slot + 1).unwrap_or_default()..)
+            .find_map(|(_, changes)| changes.get(pubkey))
+            .map(Option::as_ref)
+    }
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+fn now() -> Duration {
+    Duration::from_millis(js_sys::Date::now() as u64)
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+fn now() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
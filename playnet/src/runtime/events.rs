@@ -0,0 +1,44 @@
+// Bank events let hosts react to state changes, e.g. to deliver subscription
+// notifications, without polling the bank.
+
+use solana_sdk::{
+    account::Account, message::SanitizedMessage, pubkey::Pubkey, signature::Signature,
+    slot_history::Slot, transaction::TransactionError,
+};
+
+/// State change committed to the bank
+#[derive(Debug)]
+pub enum BankEvent<'a> {
+    /// An account was stored
+    AccountChanged {
+        pubkey: &'a Pubkey,
+        account: &'a Account,
+        slot: Slot,
+    },
+    /// A transaction was committed
+    TransactionCommitted {
+        signature: &'a Signature,
+        message: &'a SanitizedMessage,
+        err: &'a Option<TransactionError>,
+        logs: &'a [String],
+        slot: Slot,
+    },
+    /// The bank moved to a new slot
+    NewSlot {
+        slot: Slot,
+        parent: Slot,
+        root: Slot,
+        /// Highest confirmed slot, `None` if no slot is confirmed yet
+        confirmed: Option<Slot>,
+        /// Highest finalized slot, `None` if no slot is finalized yet
+        finalized: Option<Slot>,
+    },
+}
+
+/// Receives the bank events.
+///
+/// Observers are called while the bank is locked, so they must not access the
+/// bank themselves.
+pub trait BankObserver: Send + Sync {
+    fn on_event(&self, event: &BankEvent);
+}
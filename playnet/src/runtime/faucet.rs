@@ -0,0 +1,149 @@
+// Airdrops are system transfers from the faucet account rather than direct
+// balance changes, so that they end up in the transaction history like any
+// other transaction.
+//
+// The faucet keypair is generated randomly for each bank and persisted with it
+// since anyone who knows the keypair can spend the faucet's lamports without
+// the airdrop limits.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    hash::{hash, Hash},
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed, Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+
+use crate::{
+    error::{PlaynetError, Result},
+    serde::keypair,
+};
+
+use super::memo_program;
+
+/// Lamports the faucet gets funded with at genesis, banks created from a
+/// genesis config get the faucet funded by the genesis instead
+pub const FAUCET_LAMPORTS: u64 = 500_000_000 * LAMPORTS_PER_SOL;
+
+/// Seed of the faucet keypair of the banks before the keypair got persisted
+const LEGACY_FAUCET_SEED: &[u8] = b"playnet-faucet";
+
+/// Maximum lamports the faucet gives out, unlimited by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AirdropLimits {
+    /// Maximum lamports of a single airdrop
+    pub per_request_cap: Option<u64>,
+    /// Maximum lamports an account can receive in total
+    pub per_account_cap: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Faucet {
+    #[serde(with = "keypair")]
+    keypair: Keypair,
+    #[serde(skip)]
+    limits: AirdropLimits,
+    /// Total lamports airdropped to the accounts
+    #[serde(skip)]
+    airdropped: HashMap<Pubkey, u64>,
+    /// Number of airdrops, included in the airdrop memos so that identical
+    /// airdrops in the same slot don't end up with the same signature
+    #[serde(skip)]
+    airdrop_count: u64,
+}
+
+impl Default for Faucet {
+    fn default() -> Self {
+        Self {
+            keypair: Keypair::new(),
+            limits: AirdropLimits::default(),
+            airdropped: HashMap::new(),
+            airdrop_count: 0,
+        }
+    }
+}
+
+impl Faucet {
+    pub fn new(keypair: Keypair) -> Self {
+        Self {
+            keypair,
+            ..Default::default()
+        }
+    }
+
+    /// Public key of the faucet of the banks before the keypair got persisted
+    pub fn legacy_pubkey() -> Pubkey {
+        keypair_from_seed(hash(LEGACY_FAUCET_SEED).as_ref())
+            .unwrap()
+            .pubkey()
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn set_limits(&mut self, limits: AirdropLimits) {
+        self.limits = limits;
+    }
+
+    /// Checks whether airdropping `lamports` to `to` stays within the limits
+    pub fn check_limits(&self, to: &Pubkey, lamports: u64) -> Result<()> {
+        if let Some(cap) = self.limits.per_request_cap {
+            if lamports > cap {
+                return Err(PlaynetError::AirdropRequestTooLarge { lamports, cap });
+            }
+        }
+
+        if let Some(cap) = self.limits.per_account_cap {
+            let current = self.airdropped.get(to).copied().unwrap_or_default();
+            if current.saturating_add(lamports) > cap {
+                return Err(PlaynetError::AirdropLimitReached {
+                    lamports,
+                    to: *to,
+                    current,
+                    cap,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the airdrop transaction, a system transfer with a memo that
+    /// makes its signature unique
+    pub fn create_airdrop_tx(
+        &mut self,
+        to: &Pubkey,
+        lamports: u64,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        self.airdrop_count += 1;
+        let from = self.keypair.pubkey();
+        let instructions = [
+            system_instruction::transfer(&from, to, lamports),
+            Instruction::new_with_bytes(
+                memo_program::id(),
+                format!("airdrop #{}", self.airdrop_count).as_bytes(),
+                vec![],
+            ),
+        ];
+
+        Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&from),
+            &[&self.keypair],
+            recent_blockhash,
+        )
+    }
+
+    /// Records a completed airdrop
+    pub fn record_airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        let airdropped = self.airdropped.entry(*to).or_default();
+        *airdropped = airdropped.saturating_add(lamports);
+    }
+}
@@ -0,0 +1,330 @@
+// The builtin programs are wrapped so that every invocation, including the
+// cross program invocations, records a frame while the transaction executes.
+// The runtime only accepts plain function pointers for the builtins, so the
+// frames are recorded in a thread local recorder that the bank starts before
+// and finishes after executing each transaction.
+//
+// Frames only record the range of the logs they emitted. The logs get split
+// between the frames once the execution is over and all the logs are known.
+// Compute units and timings are the difference of the meters before and after
+// the invocation, so they include the inner invocations.
+//
+// While BPF tracing is enabled, the invocations of BPF programs go through a
+// tracing executor that hands the trace to the frame of the invocation.
+
+use std::{cell::RefCell, ops::Range};
+
+use serde::{Deserialize, Serialize};
+use solana_program_runtime::invoke_context::{InvokeContext, ProcessInstructionWithContext};
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey};
+
+use super::bpf_tracer::{add_tracing_executor, BpfTrace};
+
+/// A program invocation and the invocations it made
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    pub program_id: Pubkey,
+    /// Stack height of the invocation, 1 for the instructions of the transaction
+    pub depth: usize,
+    /// Compute units consumed, including the inner invocations
+    pub compute_units_consumed: u64,
+    /// Logs emitted in this invocation, the logs of the inner invocations are
+    /// in their own invocations
+    pub logs: Vec<String>,
+    /// Data the program returned
+    pub return_data: Option<Vec<u8>>,
+    pub result: Result<(), InstructionError>,
+    /// Time spent in the BPF loader, including the inner invocations
+    pub timings: ExecutionTimings,
+    /// Executed instructions of BPF programs, only recorded while BPF tracing
+    /// is enabled
+    pub trace: Option<BpfTrace>,
+    /// Invocations made by the program, in order
+    pub inner_invocations: Vec<Invocation>,
+}
+
+/// Time spent in the stages of the BPF loader, in microseconds. Native programs
+/// don't go through the loader so their timings are always zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionTimings {
+    pub get_or_create_executor_us: u64,
+    pub serialize_us: u64,
+    pub create_vm_us: u64,
+    pub execute_us: u64,
+    pub deserialize_us: u64,
+}
+
+impl ExecutionTimings {
+    fn from_invoke_context(invoke_context: &InvokeContext) -> Self {
+        let timings = &invoke_context.timings;
+        Self {
+            get_or_create_executor_us: timings.get_or_create_executor_us,
+            serialize_us: timings.serialize_us,
+            create_vm_us: timings.create_vm_us,
+            execute_us: timings.execute_us,
+            deserialize_us: timings.deserialize_us,
+        }
+    }
+
+    fn saturating_sub(&self, other: &Self) -> Self {
+        Self {
+            get_or_create_executor_us: self
+                .get_or_create_executor_us
+                .saturating_sub(other.get_or_create_executor_us),
+            serialize_us: self.serialize_us.saturating_sub(other.serialize_us),
+            create_vm_us: self.create_vm_us.saturating_sub(other.create_vm_us),
+            execute_us: self.execute_us.saturating_sub(other.execute_us),
+            deserialize_us: self.deserialize_us.saturating_sub(other.deserialize_us),
+        }
+    }
+
+    pub fn saturating_add(&self, other: &Self) -> Self {
+        Self {
+            get_or_create_executor_us: self
+                .get_or_create_executor_us
+                .saturating_add(other.get_or_create_executor_us),
+            serialize_us: self.serialize_us.saturating_add(other.serialize_us),
+            create_vm_us: self.create_vm_us.saturating_add(other.create_vm_us),
+            execute_us: self.execute_us.saturating_add(other.execute_us),
+            deserialize_us: self.deserialize_us.saturating_add(other.deserialize_us),
+        }
+    }
+}
+
+struct Frame {
+    program_id: Pubkey,
+    depth: usize,
+    /// Whether the runtime logs the invocation and its result around the
+    /// builtin, which it does when the builtin is the invoked program itself
+    runtime_logs: bool,
+    logs: Range<usize>,
+    pre_remaining_units: u64,
+    compute_units_consumed: u64,
+    pre_timings: ExecutionTimings,
+    timings: ExecutionTimings,
+    return_data: Option<Vec<u8>>,
+    result: Result<(), InstructionError>,
+    trace: Option<BpfTrace>,
+    children: Vec<Frame>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    bpf_tracing: bool,
+    /// Frames of the invocations that are still executing
+    stack: Vec<Frame>,
+    /// Frames of the instructions of the transaction
+    roots: Vec<Frame>,
+}
+
+impl Recorder {
+    fn close(&mut self, frame: Frame) {
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(frame),
+            None => self.roots.push(frame),
+        }
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Starts recording the invocations of a transaction on this thread
+pub(super) fn start_recording(bpf_tracing: bool) {
+    RECORDER.with(|recorder| {
+        *recorder.borrow_mut() = Some(Recorder {
+            bpf_tracing,
+            ..Default::default()
+        })
+    });
+}
+
+/// Stops recording and returns the invocations of the instructions of the
+/// transaction, `logs` are all the logs of the transaction
+pub(super) fn finish_recording(logs: &[String]) -> Vec<Invocation> {
+    let roots = RECORDER
+        .with(|recorder| recorder.borrow_mut().take())
+        .map(|recorder| recorder.roots)
+        .unwrap_or_default();
+
+    roots
+        .into_iter()
+        .map(|frame| into_invocation(frame, logs))
+        .collect()
+}
+
+/// Records the precompile instructions, they're verified before the execution
+/// instead of getting invoked
+pub(super) fn record_precompile(program_id: Pubkey) {
+    RECORDER.with(|recorder| {
+        if let Some(recorder) = recorder.borrow_mut().as_mut() {
+            recorder.close(Frame {
+                program_id,
+                depth: 1,
+                runtime_logs: false,
+                logs: 0..0,
+                pre_remaining_units: 0,
+                compute_units_consumed: 0,
+                pre_timings: ExecutionTimings::default(),
+                timings: ExecutionTimings::default(),
+                return_data: None,
+                result: Ok(()),
+                trace: None,
+                children: vec![],
+            });
+        }
+    });
+}
+
+/// Hands the trace of the executing BPF program to its frame
+pub(super) fn record_trace(trace: BpfTrace) {
+    RECORDER.with(|recorder| {
+        if let Some(frame) = recorder
+            .borrow_mut()
+            .as_mut()
+            .and_then(|recorder| recorder.stack.last_mut())
+        {
+            frame.trace = Some(trace);
+        }
+    });
+}
+
+/// Runs the builtin of `builtin_id` while recording the invocation of the
+/// program
+pub(super) fn record_invocation(
+    builtin_id: Pubkey,
+    first_instruction_account: usize,
+    invoke_context: &mut InvokeContext,
+    process_instruction: ProcessInstructionWithContext,
+) -> Result<(), InstructionError> {
+    let (is_recording, bpf_tracing) = RECORDER.with(|recorder| {
+        recorder
+            .borrow()
+            .as_ref()
+            .map(|recorder| (true, recorder.bpf_tracing))
+            .unwrap_or_default()
+    });
+    let program_id = invoke_context
+        .transaction_context
+        .get_current_instruction_context()
+        .and_then(|instruction_context| {
+            instruction_context.get_last_program_key(invoke_context.transaction_context)
+        })
+        .copied();
+    let program_id = match program_id {
+        Ok(program_id) if is_recording => program_id,
+        _ => return process_instruction(first_instruction_account, invoke_context),
+    };
+
+    let log_start = log_count(invoke_context);
+    let frame = Frame {
+        program_id,
+        depth: invoke_context.get_stack_height(),
+        runtime_logs: builtin_id == program_id,
+        logs: log_start..log_start,
+        pre_remaining_units: remaining_units(invoke_context),
+        compute_units_consumed: 0,
+        pre_timings: ExecutionTimings::from_invoke_context(invoke_context),
+        timings: ExecutionTimings::default(),
+        return_data: None,
+        result: Ok(()),
+        trace: None,
+        children: vec![],
+    };
+    RECORDER.with(|recorder| {
+        if let Some(recorder) = recorder.borrow_mut().as_mut() {
+            recorder.stack.push(frame);
+        }
+    });
+
+    if bpf_tracing {
+        add_tracing_executor(first_instruction_account, invoke_context);
+    }
+    let result = process_instruction(first_instruction_account, invoke_context);
+
+    let log_end = log_count(invoke_context);
+    let post_remaining_units = remaining_units(invoke_context);
+    let post_timings = ExecutionTimings::from_invoke_context(invoke_context);
+    // The runtime resets the return data before every invocation
+    let return_data = match invoke_context.transaction_context.get_return_data() {
+        (returning_program_id, data) if *returning_program_id == program_id && !data.is_empty() => {
+            Some(data.to_vec())
+        }
+        _ => None,
+    };
+    RECORDER.with(|recorder| {
+        if let Some(recorder) = recorder.borrow_mut().as_mut() {
+            if let Some(mut frame) = recorder.stack.pop() {
+                frame.logs.end = log_end;
+                frame.compute_units_consumed = frame
+                    .pre_remaining_units
+                    .saturating_sub(post_remaining_units);
+                frame.timings = post_timings.saturating_sub(&frame.pre_timings);
+                frame.return_data = return_data;
+                frame.result = result.clone();
+                recorder.close(frame);
+            }
+        }
+    });
+
+    result
+}
+
+fn log_count(invoke_context: &InvokeContext) -> usize {
+    invoke_context
+        .get_log_collector()
+        .map(|log_collector| log_collector.borrow().get_recorded_content().len())
+        .unwrap_or_default()
+}
+
+fn remaining_units(invoke_context: &InvokeContext) -> u64 {
+    invoke_context.get_compute_meter().borrow().get_remaining()
+}
+
+/// Returns the range of the logs of the frame including the invoke and result
+/// logs of the runtime. The runtime logs right before and right after the
+/// builtin, but the log collector stops recording once the logs get
+/// truncated, so a log of the runtime is only in the logs if a log got
+/// recorded after the position it would be at. The truncation log takes the
+/// place of the first log that didn't get recorded.
+fn frame_logs(frame: &Frame, logs: &[String]) -> Range<usize> {
+    let mut range = frame.logs.clone();
+    if frame.runtime_logs && range.start < logs.len() {
+        range.start = range.start.saturating_sub(1);
+    }
+    if frame.runtime_logs && range.end < logs.len() {
+        range.end += 1;
+    }
+
+    range
+}
+
+fn into_invocation(frame: Frame, logs: &[String]) -> Invocation {
+    let range = frame_logs(&frame, logs);
+    let child_ranges = frame
+        .children
+        .iter()
+        .map(|child| frame_logs(child, logs))
+        .collect::<Vec<_>>();
+    let own_logs = range
+        .filter(|index| !child_ranges.iter().any(|range| range.contains(index)))
+        .filter_map(|index| logs.get(index).cloned())
+        .collect();
+
+    Invocation {
+        program_id: frame.program_id,
+        depth: frame.depth,
+        compute_units_consumed: frame.compute_units_consumed,
+        logs: own_logs,
+        return_data: frame.return_data,
+        result: frame.result,
+        timings: frame.timings,
+        trace: frame.trace,
+        inner_invocations: frame
+            .children
+            .into_iter()
+            .map(|child| into_invocation(child, logs))
+            .collect(),
+    }
+}
@@ -0,0 +1,46 @@
+// Native implementation of the SPL Memo program (v3).
+//
+// The memo program is a builtin in Playnet, like it is preloaded on
+// `solana-test-validator`, so memos work without deploying the program. The
+// faucet also relies on it to make the airdrop transactions unique.
+
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
+use solana_sdk::instruction::InstructionError;
+
+solana_sdk::declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+pub fn process_memo_instruction(
+    _first_instruction_account: usize,
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    let transaction_context = &invoke_context.transaction_context;
+    let instruction_context = transaction_context.get_current_instruction_context()?;
+
+    // All the accounts of the instruction must sign the memo
+    let mut missing_required_signature = false;
+    for index in 0..instruction_context.get_number_of_instruction_accounts() {
+        if !instruction_context.is_instruction_account_signer(index)? {
+            let pubkey = transaction_context.get_key_of_account_at_index(
+                instruction_context.get_index_of_instruction_account_in_transaction(index)?,
+            )?;
+            ic_msg!(invoke_context, "Signer {} missing", pubkey);
+            missing_required_signature = true;
+        }
+    }
+    if missing_required_signature {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let data = instruction_context.get_instruction_data();
+    let memo = std::str::from_utf8(data).map_err(|err| {
+        ic_msg!(
+            invoke_context,
+            "Invalid UTF-8, from byte {}",
+            err.valid_up_to()
+        );
+        InstructionError::InvalidInstructionData
+    })?;
+    ic_msg!(invoke_context, "Memo (len {}): {:?}", memo.len(), memo);
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use {
+    super::invocation::record_precompile,
     serde::{Deserialize, Serialize},
     solana_program_runtime::{
         compute_budget::ComputeBudget,
@@ -118,6 +119,7 @@ impl MessageProcessor {
             }
 
             let result = if is_precompile {
+                record_precompile(*program_id);
                 invoke_context
                     .transaction_context
                     .push(program_indices, &instruction_accounts, &instruction.data)
@@ -1,5 +1,17 @@
+pub mod account_dump;
+pub mod account_source;
 pub mod bank;
+pub mod block_production;
+pub mod block_store;
+pub mod blockhash_queue;
+pub mod bpf_tracer;
+pub mod commitment;
+pub mod events;
+pub mod faucet;
+pub mod invocation;
+pub mod memo_program;
 pub mod message_processor;
 pub mod nonce_keyed_account;
+pub mod snapshot;
 pub mod system_instruction_processor;
 pub mod transaction_history;
@@ -0,0 +1,366 @@
+// Binary bank snapshots.
+//
+// A snapshot starts with a header that identifies the schema version and the
+// compression of the body, followed by the `bincode` serialized bank:
+//
+// | magic (8 bytes) | version (u32 LE) | compression (u8) | body |
+//
+// Snapshots are written to and read from streams so they never have to be
+// materialized in memory as a whole.
+
+use std::io::{self, Cursor, ErrorKind, Read, Write};
+
+use crate::error::{PlaynetError, Result};
+
+use super::bank::{PgBank, PgBankV1, PgBankV2, PgBankV3};
+
+/// Bytes every snapshot starts with
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"PGSNAP\0\0";
+
+/// Version of the snapshot schema that gets written.
+///
+/// Version history:
+/// - `0`: Legacy save data, `PgBank` serialized as a JSON string without a header
+/// - `1`: `bincode` serialized `PgBank`
+/// - `2`: Adds the transaction count, inflation and slots per year to `PgBank`
+/// - `3`: Adds the faucet keypair to `PgBank`
+/// - `4`: Adds the recent blockhash queue to `PgBank`
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+/// Compression of the snapshot body
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotCompression {
+    None,
+    /// Requires the `zstd` feature
+    Zstd,
+}
+
+impl SnapshotCompression {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zstd => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Zstd),
+            _ => Err(PlaynetError::InvalidSnapshot(format!(
+                "Unknown compression: {}",
+                byte
+            ))),
+        }
+    }
+}
+
+/// Information about the snapshot that precedes the body
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotHeader {
+    pub version: u32,
+    pub compression: SnapshotCompression,
+}
+
+impl SnapshotHeader {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&[self.compression.to_byte()])?;
+        Ok(())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let mut compression = [0];
+        reader.read_exact(&mut compression)?;
+
+        Ok(Self {
+            version: u32::from_le_bytes(version),
+            compression: SnapshotCompression::from_byte(compression[0])?,
+        })
+    }
+}
+
+/// Writes the bank snapshot with the latest schema version
+pub fn write_snapshot<W: Write>(
+    bank: &PgBank,
+    mut writer: W,
+    compression: SnapshotCompression,
+) -> Result<()> {
+    SnapshotHeader {
+        version: SNAPSHOT_VERSION,
+        compression,
+    }
+    .write(&mut writer)?;
+
+    match compression {
+        SnapshotCompression::None => Ok(bincode::serialize_into(writer, bank)?),
+        #[cfg(feature = "zstd")]
+        SnapshotCompression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+            bincode::serialize_into(&mut encoder, bank)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        #[cfg(not(feature = "zstd"))]
+        SnapshotCompression::Zstd => Err(zstd_unsupported()),
+    }
+}
+
+/// Reads a bank snapshot of any known schema version, migrating it to the
+/// latest version if necessary.
+pub fn read_snapshot<R: Read>(mut reader: R) -> Result<PgBank> {
+    let mut magic = [0; SNAPSHOT_MAGIC.len()];
+    let read_len = read_up_to(&mut reader, &mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        // Snapshots without a header are the legacy JSON save data
+        let reader = Cursor::new(&magic[..read_len]).chain(reader);
+        return migrate_v0(reader);
+    }
+
+    let header = SnapshotHeader::read(&mut reader)?;
+    match header.compression {
+        SnapshotCompression::None => read_body(header.version, reader),
+        #[cfg(feature = "zstd")]
+        SnapshotCompression::Zstd => {
+            read_body(header.version, zstd::stream::read::Decoder::new(reader)?)
+        }
+        #[cfg(not(feature = "zstd"))]
+        SnapshotCompression::Zstd => Err(zstd_unsupported()),
+    }
+}
+
+/// Reads the snapshot header, `None` for the legacy JSON save data
+pub fn read_snapshot_header<R: Read>(mut reader: R) -> Result<Option<SnapshotHeader>> {
+    let mut magic = [0; SNAPSHOT_MAGIC.len()];
+    read_up_to(&mut reader, &mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Ok(None);
+    }
+
+    SnapshotHeader::read(&mut reader).map(Some)
+}
+
+fn read_body<R: Read>(version: u32, reader: R) -> Result<PgBank> {
+    match version {
+        0 => migrate_v0(reader),
+        1 => migrate_v1(reader),
+        2 => migrate_v2(reader),
+        3 => migrate_v3(reader),
+        4 => Ok(bincode::deserialize_from::<_, PgBank>(reader)?.init()),
+        _ => Err(PlaynetError::InvalidSnapshot(format!(
+            "Unsupported version: {} (latest: {})",
+            version, SNAPSHOT_VERSION
+        ))),
+    }
+}
+
+/// Version 0 is the JSON serialized bank
+fn migrate_v0<R: Read>(reader: R) -> Result<PgBank> {
+    Ok(serde_json::from_reader::<_, PgBank>(reader)?.init())
+}
+
+/// Version 1 doesn't have the fields that were added in version 2
+fn migrate_v1<R: Read>(reader: R) -> Result<PgBank> {
+    Ok(PgBank::from(bincode::deserialize_from::<_, PgBankV1>(reader)?).init())
+}
+
+/// Version 2 doesn't have the faucet keypair, a new one gets generated
+fn migrate_v2<R: Read>(reader: R) -> Result<PgBank> {
+    Ok(PgBank::from(bincode::deserialize_from::<_, PgBankV2>(reader)?).init())
+}
+
+/// Version 3 doesn't have the blockhash queue, it starts with the latest
+/// blockhash
+fn migrate_v3<R: Read>(reader: R) -> Result<PgBank> {
+    Ok(PgBank::from(bincode::deserialize_from::<_, PgBankV3>(reader)?).init())
+}
+
+/// Same as `Read::read_exact` but doesn't fail if the reader has less bytes
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read_len = 0;
+    while read_len < buf.len() {
+        match reader.read(&mut buf[read_len..]) {
+            Ok(0) => break,
+            Ok(len) => read_len += len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(read_len)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unsupported() -> PlaynetError {
+    PlaynetError::InvalidSnapshot("zstd compression requires the `zstd` feature".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use solana_sdk::{
+        account::Account, epoch_schedule::EpochSchedule, fee_calculator::FeeRateGovernor,
+        hash::Hash, pubkey::Pubkey, rent::Rent,
+    };
+
+    use crate::runtime::{block_production::BlockProduction, faucet::FAUCET_LAMPORTS};
+
+    use super::*;
+
+    fn create_bank() -> (PgBank, Pubkey) {
+        let mut bank = PgBank::new(None).unwrap();
+        bank.set_block_production(BlockProduction::Manual);
+        let pubkey = Pubkey::new_unique();
+        bank.request_airdrop(&pubkey, 1_000_000).unwrap();
+        bank.advance_slots(3);
+        (bank, pubkey)
+    }
+
+    fn assert_same_bank(bank: &PgBank, read_bank: &PgBank, pubkey: &Pubkey) {
+        assert_eq!(read_bank.get_slot(), bank.get_slot());
+        assert_eq!(read_bank.get_block_height(), bank.get_block_height());
+        assert_eq!(read_bank.get_genesis_hash(), bank.get_genesis_hash());
+        assert_eq!(
+            read_bank.get_latest_blockhash(),
+            bank.get_latest_blockhash()
+        );
+        assert_eq!(read_bank.get_faucet_pubkey(), bank.get_faucet_pubkey());
+        assert_eq!(read_bank.get_capitalization(), bank.get_capitalization());
+        assert_eq!(read_bank.get_account(pubkey), bank.get_account(pubkey));
+    }
+
+    fn round_trip(compression: SnapshotCompression) {
+        let (bank, pubkey) = create_bank();
+        let mut snapshot = vec![];
+        write_snapshot(&bank, &mut snapshot, compression).unwrap();
+
+        assert_eq!(
+            read_snapshot_header(snapshot.as_slice()).unwrap(),
+            Some(SnapshotHeader {
+                version: SNAPSHOT_VERSION,
+                compression,
+            })
+        );
+        assert_same_bank(&bank, &read_snapshot(snapshot.as_slice()).unwrap(), &pubkey);
+    }
+
+    fn header(version: u32, compression: u8) -> Vec<u8> {
+        let mut snapshot = SNAPSHOT_MAGIC.to_vec();
+        snapshot.extend_from_slice(&version.to_le_bytes());
+        snapshot.push(compression);
+        snapshot
+    }
+
+    #[test]
+    fn round_trip_uncompressed() {
+        round_trip(SnapshotCompression::None);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn round_trip_zstd() {
+        round_trip(SnapshotCompression::Zstd);
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn zstd_requires_feature() {
+        let (bank, _) = create_bank();
+        assert!(matches!(
+            write_snapshot(&bank, vec![], SnapshotCompression::Zstd),
+            Err(PlaynetError::InvalidSnapshot(_))
+        ));
+        assert!(matches!(
+            read_snapshot(header(SNAPSHOT_VERSION, 1).as_slice()),
+            Err(PlaynetError::InvalidSnapshot(_))
+        ));
+    }
+
+    #[test]
+    fn read_v0() {
+        let (bank, pubkey) = create_bank();
+        let snapshot = serde_json::to_vec(&bank).unwrap();
+
+        assert_eq!(read_snapshot_header(snapshot.as_slice()).unwrap(), None);
+        assert_same_bank(&bank, &read_snapshot(snapshot.as_slice()).unwrap(), &pubkey);
+    }
+
+    #[test]
+    fn read_v1() {
+        let pubkey = Pubkey::new_unique();
+        let account = Account::new(1_000_000, 0, &Pubkey::default());
+        let genesis_hash = Hash::new_unique();
+        let latest_blockhash = Hash::new_unique();
+        let rent = Rent {
+            lamports_per_byte_year: 1,
+            ..Rent::default()
+        };
+        let epoch_schedule = EpochSchedule::custom(64, 64, false);
+        let fee_rate_governor = FeeRateGovernor::new(5000, 0);
+
+        // Fields of `PgBankV1` in order
+        let mut snapshot = header(1, 0);
+        bincode::serialize_into(
+            &mut snapshot,
+            &(
+                HashMap::from([(pubkey, account.clone())]),
+                7u64,
+                5u64,
+                genesis_hash,
+                latest_blockhash,
+                1_600_000_000i64,
+                rent,
+                epoch_schedule,
+                fee_rate_governor,
+            ),
+        )
+        .unwrap();
+
+        let bank = read_snapshot(snapshot.as_slice()).unwrap();
+        assert_eq!(bank.get_slot(), 7);
+        assert_eq!(bank.get_block_height(), 5);
+        assert_eq!(bank.get_genesis_hash(), genesis_hash);
+        assert_eq!(bank.get_latest_blockhash(), latest_blockhash);
+        assert!(bank.is_blockhash_valid(&latest_blockhash));
+        assert_eq!(bank.get_epoch_schedule(), &epoch_schedule);
+        assert_eq!(bank.get_lamports_per_signature(), 5000);
+        assert_eq!(bank.get_account(&pubkey), Some(&account));
+        assert_eq!(
+            bank.get_account(&bank.get_faucet_pubkey())
+                .map(|account| account.lamports),
+            Some(FAUCET_LAMPORTS)
+        );
+    }
+
+    #[test]
+    fn reject_bad_magic() {
+        let mut snapshot = header(SNAPSHOT_VERSION, 0);
+        snapshot[0] = b'X';
+
+        assert_eq!(read_snapshot_header(snapshot.as_slice()).unwrap(), None);
+        assert!(read_snapshot(snapshot.as_slice()).is_err());
+        assert!(read_snapshot(&b"PGSNAP"[..]).is_err());
+    }
+
+    #[test]
+    fn reject_unknown_version() {
+        let snapshot = header(SNAPSHOT_VERSION + 1, 0);
+        assert!(matches!(
+            read_snapshot(snapshot.as_slice()),
+            Err(PlaynetError::InvalidSnapshot(_))
+        ));
+    }
+
+    #[test]
+    fn reject_unknown_compression() {
+        let snapshot = header(SNAPSHOT_VERSION, 2);
+        assert!(matches!(
+            read_snapshot(snapshot.as_slice()),
+            Err(PlaynetError::InvalidSnapshot(_))
+        ));
+    }
+}
@@ -3,11 +3,22 @@ use solana_sdk::{
     clock::UnixTimestamp,
     instruction::CompiledInstruction,
     message::v0::LoadedAddresses,
+    pubkey,
     pubkey::Pubkey,
     slot_history::Slot,
     transaction::{TransactionError, VersionedTransaction},
 };
-use wasm_bindgen::prelude::*;
+
+use super::{
+    invocation::{ExecutionTimings, Invocation},
+    memo_program,
+};
+
+/// SPL Memo program ids, v1 and v3
+const MEMO_PROGRAM_IDS: [Pubkey; 2] = [
+    pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"),
+    memo_program::ID,
+];
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TransactionData {
@@ -51,89 +62,104 @@ impl TransactionData {
     pub fn get_block_time(&self) -> Option<UnixTimestamp> {
         self.block_time
     }
+
+    /// Returns the memos of the SPL Memo instructions in the same format as
+    /// the Solana RPC, `None` if the transaction doesn't have any memos
+    pub fn get_memo(&self) -> Option<String> {
+        let account_keys = self.tx.message.static_account_keys();
+        let memos = self
+            .tx
+            .message
+            .instructions()
+            .iter()
+            .filter(|ix| {
+                account_keys
+                    .get(ix.program_id_index as usize)
+                    .map(|program_id| MEMO_PROGRAM_IDS.contains(program_id))
+                    .unwrap_or(false)
+            })
+            .map(|ix| {
+                let memo =
+                    String::from_utf8(ix.data.clone()).unwrap_or_else(|_| "(unparseable)".into());
+                format!("[{}] {}", ix.data.len(), memo)
+            })
+            .collect::<Vec<_>>();
+
+        if memos.is_empty() {
+            None
+        } else {
+            Some(memos.join("; "))
+        }
+    }
 }
 
 /// Metadata for a confirmed transaction on the ledger
-#[wasm_bindgen]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ConfirmedTransactionMeta {
     /// The fee charged for processing the transaction
-    pub(crate) fee: u64,
+    pub fee: u64,
     /// An array of cross program invoked instructions
-    pub(crate) inner_instructions: Option<Vec<CompiledInnerInstruction>>,
+    pub inner_instructions: Option<Vec<CompiledInnerInstruction>>,
     /// The balances of the transaction accounts before processing
-    pub(crate) pre_balances: Vec<u64>,
+    pub pre_balances: Vec<u64>,
     /// The balances of the transaction accounts after processing
-    pub(crate) post_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
     /// An array of program log messages emitted during a transaction
-    pub(crate) log_messages: Option<Vec<String>>,
+    pub log_messages: Option<Vec<String>>,
     /// The token balances of the transaction accounts before processing
-    pub(crate) pre_token_balances: Option<Vec<TokenBalance>>,
+    pub pre_token_balances: Option<Vec<TokenBalance>>,
     /// The token balances of the transaction accounts after processing
-    pub(crate) post_token_balances: Option<Vec<TokenBalance>>,
+    pub post_token_balances: Option<Vec<TokenBalance>>,
     /// The error result of transaction processing
-    pub(crate) err: Option<TransactionError>,
+    pub err: Option<TransactionError>,
     /// The collection of addresses loaded using address lookup tables
-    pub(crate) loaded_addresses: Option<LoadedAddresses>,
+    pub loaded_addresses: Option<LoadedAddresses>,
     /// The compute units consumed after processing the transaction
-    pub(crate) compute_units_consumed: Option<u64>,
+    pub compute_units_consumed: Option<u64>,
+    /// The compute units consumed by each instruction and its cross program
+    /// invocations
+    #[serde(default)]
+    pub instruction_compute_units: Option<Vec<InstructionComputeUnits>>,
+    /// The execution details of each invoked program
+    #[serde(default)]
+    pub program_details: Option<Vec<ProgramExecutionDetails>>,
 }
 
-#[wasm_bindgen]
-impl ConfirmedTransactionMeta {
-    pub fn fee(&self) -> u64 {
-        self.fee
-    }
-
-    /// TODO:
-    #[wasm_bindgen(js_name = innerInstructions)]
-    pub fn inner_instructions(&self) -> Option<u8> {
-        None
-    }
-
-    #[wasm_bindgen(js_name = preBalances)]
-    pub fn pre_balances(&self) -> Vec<u64> {
-        self.pre_balances.to_owned()
-    }
-
-    #[wasm_bindgen(js_name = postBalances)]
-    pub fn post_balances(&self) -> Vec<u64> {
-        self.post_balances.to_owned()
-    }
-
-    #[wasm_bindgen(js_name = logs)]
-    pub fn log_messages(&self) -> Option<Vec<JsValue>> {
-        self.log_messages
-            .as_ref()
-            .map(|logs| logs.iter().map(|log| JsValue::from_str(&log)).collect())
-    }
-
-    /// TODO:
-    #[wasm_bindgen(js_name = preTokenBalances)]
-    pub fn pre_token_balances(&self) -> Option<u8> {
-        None
-    }
-
-    /// TODO:
-    #[wasm_bindgen(js_name = postTokenBalances)]
-    pub fn post_token_balances(&self) -> Option<u8> {
-        None
-    }
-
-    pub fn err(&self) -> Option<String> {
-        self.err.as_ref().map(|err| err.to_string())
-    }
+/// Compute units consumed by an instruction and the instructions it invoked
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstructionComputeUnits {
+    pub program_id: Pubkey,
+    /// Includes the units of the inner instructions
+    pub units_consumed: u64,
+    pub inner_instructions: Vec<InstructionComputeUnits>,
+}
 
-    /// TODO:
-    #[wasm_bindgen(js_name = loadedAddresses)]
-    pub fn loaded_addresses(&self) -> Option<u8> {
-        None
+impl From<&Invocation> for InstructionComputeUnits {
+    fn from(invocation: &Invocation) -> Self {
+        Self {
+            program_id: invocation.program_id,
+            units_consumed: invocation.compute_units_consumed,
+            inner_instructions: invocation
+                .inner_invocations
+                .iter()
+                .map(Self::from)
+                .collect(),
+        }
     }
+}
 
-    #[wasm_bindgen(js_name = computeUnitsConsumed)]
-    pub fn compute_units_consumed(&self) -> Option<u64> {
-        self.compute_units_consumed
-    }
+/// Execution details of a program, summed over the instructions of the
+/// transaction that invoked it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProgramExecutionDetails {
+    pub program_id: Pubkey,
+    /// Number of successful instructions
+    pub count: u32,
+    /// Compute units consumed by the successful instructions
+    pub units_consumed: u64,
+    /// Compute units consumed by the failed instructions
+    pub errored_units: u64,
+    pub timings: ExecutionTimings,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -3,8 +3,9 @@
 use std::{collections::HashMap, str::FromStr};
 
 use serde::{
+    de::Error,
     ser::{SerializeMap, Serializer},
-    Deserialize, Deserializer,
+    Deserialize, Deserializer, Serialize,
 };
 
 /// `Pubkey` is getting de/serialized as bytes but JSON keys must be strings.
@@ -18,10 +19,16 @@ pub mod bank_accounts {
 
     /// `Pubkey` as key is getting serialized as bytes by default. This function
     /// serializes `Pubkey`s as `String`s to make `serde_json::to_string` work.
+    ///
+    /// Non human-readable formats such as `bincode` keep the bytes.
     pub fn serialize<S>(accounts: &BankAccounts, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            return accounts.serialize(serializer);
+        }
+
         let mut map = serializer.serialize_map(Some(accounts.len()))?;
         for (k, v) in accounts {
             map.serialize_entry(&k.to_string(), v)?;
@@ -33,12 +40,54 @@ pub mod bank_accounts {
     where
         D: Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return BankAccounts::deserialize(deserializer);
+        }
+
         let mut pubkey_hm = HashMap::new();
         let string_hm = HashMap::<String, Account>::deserialize(deserializer)?;
         for (s, acc) in string_hm {
-            pubkey_hm.insert(Pubkey::from_str(&s).unwrap(), acc);
+            let pubkey = Pubkey::from_str(&s)
+                .map_err(|err| D::Error::custom(format!("Invalid public key `{}`: {}", s, err)))?;
+            pubkey_hm.insert(pubkey, acc);
         }
 
         Ok(pubkey_hm)
     }
 }
+
+/// `Keypair` doesn't implement de/serialization. Human-readable formats store
+/// the keypair as a base58 string, others as bytes.
+pub mod keypair {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+
+    pub fn serialize<S>(keypair: &Keypair, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&keypair.to_base58_string())
+        } else {
+            serializer.serialize_bytes(&keypair.to_bytes())
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Keypair, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            bs58::decode(&s)
+                .into_vec()
+                .map_err(|err| D::Error::custom(format!("Invalid keypair: {}", err)))?
+        } else {
+            Vec::<u8>::deserialize(deserializer)?
+        };
+
+        Keypair::from_bytes(&bytes)
+            .map_err(|err| D::Error::custom(format!("Invalid keypair: {}", err)))
+    }
+}
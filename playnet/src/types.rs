@@ -1,98 +1,56 @@
+use std::{collections::HashMap, str::FromStr};
+
 use solana_sdk::{
     account::Account,
-    clock::UnixTimestamp,
+    bpf_loader, bpf_loader_deprecated,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::{Epoch, UnixTimestamp},
     hash::Hash,
     pubkey::Pubkey,
+    signature::Signature,
     slot_history::Slot,
-    stake_history::Epoch,
-    transaction::{self, TransactionError, TransactionVersion},
+    transaction::{self, TransactionError},
     transaction_context::{TransactionAccount, TransactionReturnData},
 };
-use wasm_bindgen::prelude::*;
-
-use crate::runtime::transaction_history::{ConfirmedTransactionMeta, TransactionData};
-
-#[wasm_bindgen]
-pub struct WasmAccount {
-    /// Lamports in the account
-    pub lamports: u64,
-    /// Data held in this account
-    #[wasm_bindgen(getter_with_clone)]
-    pub data: Vec<u8>,
-    /// The program that owns this account. If executable, the program that loads this account.
-    pub owner: Pubkey,
-    /// This account's data contains a loaded program (and is now read-only)
-    pub executable: bool,
-    /// The epoch at which this account will next owe rent
-    #[wasm_bindgen(js_name = rentEpoch)]
-    pub rent_epoch: Epoch,
-}
-
-impl From<Account> for WasmAccount {
-    fn from(account: Account) -> Self {
-        Self {
-            lamports: account.lamports,
-            data: account.data,
-            owner: account.owner,
-            executable: account.executable,
-            rent_epoch: account.rent_epoch,
-        }
-    }
-}
 
-#[wasm_bindgen]
-pub struct GetLatestBlockhashResult {
-    blockhash: Hash,
-    last_valid_block_height: u64,
-}
-
-impl GetLatestBlockhashResult {
-    pub fn new(blockhash: Hash, last_valid_block_height: u64) -> Self {
-        Self {
-            blockhash,
-            last_valid_block_height,
-        }
-    }
-}
-
-#[wasm_bindgen]
-impl GetLatestBlockhashResult {
-    pub fn blockhash(&self) -> String {
-        self.blockhash.to_string()
-    }
-
-    #[wasm_bindgen(js_name = lastValidBlockHeight)]
-    pub fn last_valid_block_height(&self) -> u64 {
-        self.last_valid_block_height
-    }
-}
+use crate::{
+    error::PlaynetError,
+    runtime::{
+        block_store::Block,
+        invocation::Invocation,
+        transaction_history::{
+            CompiledInnerInstruction, InstructionComputeUnits, ProgramExecutionDetails,
+            TransactionData,
+        },
+    },
+};
 
-/// Return data at the end of a transaction
-#[wasm_bindgen]
-pub struct WasmTransactionReturnData {
-    #[wasm_bindgen(js_name = programId)]
-    pub program_id: Pubkey,
-    #[wasm_bindgen(getter_with_clone)]
-    pub data: Vec<u8>,
+/// Latest blockhash and the last block height it's valid at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatestBlockhash {
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
 }
 
-impl From<TransactionReturnData> for WasmTransactionReturnData {
-    fn from(val: TransactionReturnData) -> Self {
-        Self {
-            data: val.data,
-            program_id: val.program_id,
-        }
-    }
-}
-
-#[wasm_bindgen]
+#[derive(Clone, Debug)]
 pub struct SimulateTransactionResult {
-    pub(crate) result: transaction::Result<()>,
-    pub(crate) pre_accounts: Vec<TransactionAccount>,
-    pub(crate) post_accounts: Vec<TransactionAccount>,
-    pub(crate) logs: Vec<String>,
-    pub(crate) units_consumed: u64,
-    pub(crate) return_data: Option<TransactionReturnData>,
+    pub result: transaction::Result<()>,
+    pub pre_accounts: Vec<TransactionAccount>,
+    pub post_accounts: Vec<TransactionAccount>,
+    pub logs: Vec<String>,
+    pub units_consumed: u64,
+    pub return_data: Option<TransactionReturnData>,
+    /// Cross program invocations of the instructions that made any
+    pub inner_instructions: Option<Vec<CompiledInnerInstruction>>,
+    /// The blockhash the transaction got simulated with if it was replaced
+    pub replacement_blockhash: Option<LatestBlockhash>,
+    /// The requested accounts after the simulation, `None` for the accounts
+    /// that don't exist
+    pub accounts: Option<Vec<Option<Account>>>,
+    /// Invocation trees of the executed instructions
+    pub invocations: Vec<Invocation>,
+    /// Execution details of the programs of the transaction instructions
+    pub program_details: Vec<ProgramExecutionDetails>,
 }
 
 impl SimulateTransactionResult {
@@ -103,6 +61,7 @@ impl SimulateTransactionResult {
         logs: Vec<String>,
         units_consumed: u64,
         return_data: Option<TransactionReturnData>,
+        inner_instructions: Option<Vec<CompiledInnerInstruction>>,
     ) -> Self {
         Self {
             result,
@@ -111,9 +70,23 @@ impl SimulateTransactionResult {
             logs,
             units_consumed,
             return_data,
+            inner_instructions,
+            replacement_blockhash: None,
+            accounts: None,
+            invocations: vec![],
+            program_details: vec![],
         }
     }
 
+    /// Returns the compute units consumed by each instruction and its cross
+    /// program invocations
+    pub fn instruction_compute_units(&self) -> Vec<InstructionComputeUnits> {
+        self.invocations
+            .iter()
+            .map(InstructionComputeUnits::from)
+            .collect()
+    }
+
     pub fn new_error(err: transaction::TransactionError) -> Self {
         Self {
             result: Err(err),
@@ -122,101 +95,153 @@ impl SimulateTransactionResult {
             post_accounts: vec![],
             units_consumed: 0,
             return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+            accounts: None,
+            invocations: vec![],
+            program_details: vec![],
         }
     }
 }
 
-#[wasm_bindgen]
-impl SimulateTransactionResult {
-    pub fn error(&self) -> Option<String> {
-        match &self.result {
-            Ok(_) => None,
-            Err(err) => Some(err.to_string()),
-        }
-    }
-
-    pub fn logs(&self) -> Vec<JsValue> {
-        self.logs.iter().map(|log| JsValue::from_str(log)).collect()
-    }
+/// Options of the transaction simulation
+#[derive(Clone, Debug, Default)]
+pub struct SimulateTransactionConfig {
+    /// Verify the signatures, can't be used with `replace_recent_blockhash`
+    pub sig_verify: bool,
+    /// Simulate with the latest blockhash instead of the one of the transaction
+    pub replace_recent_blockhash: bool,
+    /// Accounts to return after the simulation
+    pub accounts: Option<Vec<Pubkey>>,
+    /// Return the cross program invocations
+    pub inner_instructions: bool,
+    /// Fail if the bank hasn't reached this slot
+    pub min_context_slot: Option<Slot>,
+    /// Account states to simulate against instead of the bank state
+    pub state_overrides: StateOverrides,
+    /// Log limit to simulate with instead of the one of the bank
+    pub log_limit: Option<LogLimit>,
+}
 
-    #[wasm_bindgen(js_name = unitsConsumed)]
-    pub fn units_consumed(&self) -> u64 {
-        self.units_consumed
-    }
+/// Same limit as the validators
+pub const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
 
-    #[wasm_bindgen(js_name = returnData)]
-    pub fn return_data(&self) -> Option<WasmTransactionReturnData> {
-        self.return_data
-            .as_ref()
-            .map(|tx_return_data| WasmTransactionReturnData::from(tx_return_data.to_owned()))
-    }
+/// Maximum bytes of log messages a transaction can emit, the logs after the
+/// limit are replaced with a single "Log truncated" message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLimit {
+    Bytes(usize),
+    /// Keep all the logs, for debugging
+    Unlimited,
 }
 
-#[wasm_bindgen]
-pub struct SendTransactionResult {
-    result: transaction::Result<String>,
+impl Default for LogLimit {
+    fn default() -> Self {
+        Self::Bytes(LOG_MESSAGES_BYTES_LIMIT)
+    }
 }
 
-impl SendTransactionResult {
-    pub fn new(tx_hash: String) -> Self {
-        Self {
-            result: Ok(tx_hash),
+impl LogLimit {
+    /// Returns the limit in the format of `LogCollector`
+    pub fn bytes(&self) -> Option<usize> {
+        match self {
+            Self::Bytes(bytes) => Some(*bytes),
+            Self::Unlimited => None,
         }
     }
+}
 
-    pub fn new_error(err: TransactionError) -> Self {
-        Self { result: Err(err) }
-    }
+/// Account fields to override, `None` fields are kept as they are
+#[derive(Clone, Debug, Default)]
+pub struct AccountOverride {
+    pub lamports: Option<u64>,
+    pub data: Option<Vec<u8>>,
+    pub owner: Option<Pubkey>,
+    pub executable: Option<bool>,
 }
 
-#[wasm_bindgen]
-impl SendTransactionResult {
-    pub fn error(&self) -> Option<String> {
-        match &self.result {
-            Ok(_) => None,
-            Err(err) => Some(err.to_string()),
+impl AccountOverride {
+    pub fn apply(&self, account: &mut Account) {
+        if let Some(lamports) = self.lamports {
+            account.lamports = lamports;
+        }
+        if let Some(data) = &self.data {
+            account.data = data.clone();
+        }
+        if let Some(owner) = self.owner {
+            account.owner = owner;
+        }
+        if let Some(executable) = self.executable {
+            account.executable = executable;
         }
-    }
-
-    #[wasm_bindgen(js_name = txHash)]
-    pub fn tx_hash(&self) -> String {
-        self.result.as_ref().unwrap().to_owned()
     }
 }
 
-#[wasm_bindgen]
-pub struct GetSignatureStatusesResult {
-    statuses: Vec<Option<TransactionStatus>>,
+/// Accounts and programs to use while loading a transaction, without
+/// mutating the bank
+#[derive(Clone, Debug, Default)]
+pub struct StateOverrides {
+    /// Account overrides, non-existent accounts are created from the default
+    /// account
+    pub accounts: HashMap<Pubkey, AccountOverride>,
+    /// ELFs to substitute, keyed by program id
+    pub programs: HashMap<Pubkey, Vec<u8>>,
 }
 
-impl GetSignatureStatusesResult {
-    pub fn new(statuses: Vec<Option<TransactionStatus>>) -> Self {
-        Self { statuses }
-    }
-}
+impl StateOverrides {
+    /// Applies the overrides to the loaded `account`, `None` if the account
+    /// doesn't exist and isn't overridden.
+    ///
+    /// Upgradeable programs keep their ELF in the program data account, which
+    /// is why the program data accounts of the substituted programs get the
+    /// new ELF.
+    pub fn apply(&self, pubkey: &Pubkey, account: Option<Account>) -> Option<Account> {
+        let account_override = self.accounts.get(pubkey);
+        let mut account = match account {
+            Some(account) => account,
+            None if account_override.is_some() => Account::default(),
+            None => return None,
+        };
+        if let Some(account_override) = account_override {
+            account_override.apply(&mut account);
+        }
 
-#[wasm_bindgen]
-impl GetSignatureStatusesResult {
-    pub fn statuses(self) -> Vec<JsValue> {
-        self.statuses
-            .into_iter()
-            .map(|status| JsValue::from(status))
-            .collect()
+        if bpf_loader::check_id(&account.owner) || bpf_loader_deprecated::check_id(&account.owner) {
+            if let Some(elf) = self.programs.get(pubkey) {
+                account.data = elf.clone();
+            }
+        } else if bpf_loader_upgradeable::check_id(&account.owner) {
+            let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+            let elf = self.programs.iter().find_map(|(program_id, elf)| {
+                let (programdata_address, _) = Pubkey::find_program_address(
+                    &[program_id.as_ref()],
+                    &bpf_loader_upgradeable::id(),
+                );
+                (programdata_address == *pubkey).then_some(elf)
+            });
+            if let Some(elf) = elf {
+                if account.data.len() >= metadata_len {
+                    account.data.truncate(metadata_len);
+                    account.data.extend_from_slice(elf);
+                }
+            }
+        }
+
+        Some(account)
     }
 }
 
-#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TransactionStatus {
-    #[wasm_bindgen(js_name = confirmationStatus)]
-    pub confirmation_status: Option<WasmCommitmentLevel>,
+    pub confirmation_status: Option<CommitmentLevel>,
     pub confirmations: Option<usize>,
     pub slot: Slot,
-    err: Option<TransactionError>,
+    pub err: Option<TransactionError>,
 }
 
 impl TransactionStatus {
     pub fn new(
-        confirmation_status: Option<WasmCommitmentLevel>,
+        confirmation_status: Option<CommitmentLevel>,
         confirmations: Option<usize>,
         slot: Slot,
         err: Option<TransactionError>,
@@ -230,64 +255,92 @@ impl TransactionStatus {
     }
 }
 
-#[wasm_bindgen]
-impl TransactionStatus {
-    pub fn error(&self) -> Option<String> {
-        self.err.as_ref().and_then(|err| Some(err.to_string()))
-    }
+/// Lamport supply of the bank.
+///
+/// Playnet doesn't have non-circulating accounts so all the supply is circulating.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Supply {
+    pub total: u64,
+    pub circulating: u64,
+    pub non_circulating: u64,
+    pub non_circulating_accounts: Vec<Pubkey>,
 }
 
-#[wasm_bindgen]
-pub struct GetTransactionResult {
-    data: Option<TransactionData>,
+/// Inflation rates of the current epoch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InflationRate {
+    pub total: f64,
+    pub validator: f64,
+    pub foundation: f64,
+    pub epoch: Epoch,
 }
 
-impl GetTransactionResult {
-    pub fn new(data: Option<TransactionData>) -> Self {
-        Self { data }
-    }
+/// Produced block with its transactions
+#[derive(Clone, Debug)]
+pub struct ConfirmedBlock {
+    pub block: Block,
+    pub transactions: Vec<TransactionData>,
 }
 
-#[wasm_bindgen]
-impl GetTransactionResult {
-    /// NOTE: This method should be called before accessing any other data
-    pub fn exists(&self) -> bool {
-        self.data.is_some()
-    }
+/// Transaction that references an address
+#[derive(Clone, Debug)]
+pub struct SignatureInfo {
+    pub signature: Signature,
+    pub slot: Slot,
+    pub confirmation_status: Option<CommitmentLevel>,
+    pub err: Option<TransactionError>,
+    pub memo: Option<String>,
+    pub block_time: Option<UnixTimestamp>,
+}
 
-    #[wasm_bindgen(js_name = blockTime)]
-    pub fn block_time(&self) -> Option<UnixTimestamp> {
-        self.data.as_ref().unwrap().get_block_time()
-    }
+/// Ordered from the least to the most committed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommitmentLevel {
+    Processed,
+    Confirmed,
+    Finalized,
+}
 
-    /// Returns the transaction version or `None` for legacy transactions
-    pub fn version(&self) -> Option<u8> {
-        match self.data.as_ref().unwrap().get_tx().version() {
-            TransactionVersion::Legacy(_) => None,
-            TransactionVersion::Number(version) => Some(version),
-        }
-    }
+/// Encoding of the wire format transactions sent by the clients
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionBinaryEncoding {
+    Base58,
+    Base64,
+}
 
-    pub fn meta(&self) -> ConfirmedTransactionMeta {
-        self.data
-            .as_ref()
-            .unwrap()
-            .get_meta()
-            .as_ref()
-            .unwrap()
-            .clone()
+impl FromStr for TransactionBinaryEncoding {
+    type Err = PlaynetError;
+
+    fn from_str(encoding: &str) -> Result<Self, Self::Err> {
+        match encoding {
+            "base58" => Ok(Self::Base58),
+            "base64" => Ok(Self::Base64),
+            _ => Err(PlaynetError::InvalidParam(format!(
+                "unsupported encoding: {}. Supported encodings: base58, base64",
+                encoding
+            ))),
+        }
     }
+}
 
-    /// Returns the base64 encoded tx string
-    pub fn transaction(&self) -> String {
-        base64::encode(bincode::serialize(&self.data.as_ref().unwrap().get_tx()).unwrap())
-    }
+/// Filter for the program accounts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountFilter {
+    /// Account data length must be equal to the given size
+    DataSize(u64),
+    /// Account data must contain the bytes at the given offset
+    Memcmp { offset: usize, bytes: Vec<u8> },
 }
 
-#[wasm_bindgen]
-#[derive(Clone, Copy)]
-pub enum WasmCommitmentLevel {
-    Processed,
-    Confirmed,
-    Finalized,
+impl AccountFilter {
+    pub fn matches(&self, account: &Account) -> bool {
+        match self {
+            Self::DataSize(size) => account.data.len() as u64 == *size,
+            Self::Memcmp { offset, bytes } => account
+                .data
+                .get(*offset..)
+                .map(|data| data.starts_with(bytes))
+                .unwrap_or(false),
+        }
+    }
 }
@@ -1,21 +1,100 @@
+use std::{any::type_name, str::FromStr};
+
+use bincode::Options;
 use solana_sdk::{
     hash::{Hash, Hasher},
-    transaction::{self, MessageHash, SanitizedTransaction, Transaction, VersionedTransaction},
+    message::AddressLoader,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{MessageHash, SanitizedTransaction, VersionedTransaction},
+};
+
+use crate::{
+    error::{PlaynetError, Result},
+    types::TransactionBinaryEncoding,
 };
 
-use crate::runtime::bank::PgAddressLoader;
+/// Maximum length of a base58 encoded transaction that fits in a packet
+const MAX_BASE58_SIZE: usize = 1683;
+/// Maximum length of a base64 encoded transaction that fits in a packet
+const MAX_BASE64_SIZE: usize = 1644;
+
+/// Decodes a base58 or base64 encoded wire format transaction.
+///
+/// Both the encoded and the decoded transaction must fit in a packet.
+pub fn decode_tx(
+    encoded_tx: &str,
+    encoding: TransactionBinaryEncoding,
+) -> Result<VersionedTransaction> {
+    let (name, max_size) = match encoding {
+        TransactionBinaryEncoding::Base58 => ("base58", MAX_BASE58_SIZE),
+        TransactionBinaryEncoding::Base64 => ("base64", MAX_BASE64_SIZE),
+    };
+    if encoded_tx.len() > max_size {
+        return Err(PlaynetError::InvalidWireTransaction(format!(
+            "{} encoded {} too large: {} bytes (max: encoded/raw {}/{})",
+            name,
+            type_name::<VersionedTransaction>(),
+            encoded_tx.len(),
+            max_size,
+            PACKET_DATA_SIZE,
+        )));
+    }
+
+    let wire_tx = match encoding {
+        TransactionBinaryEncoding::Base58 => bs58::decode(encoded_tx)
+            .into_vec()
+            .map_err(|err| format!("invalid base58 encoding: {:?}", err)),
+        TransactionBinaryEncoding::Base64 => {
+            base64::decode(encoded_tx).map_err(|err| format!("invalid base64 encoding: {:?}", err))
+        }
+    }
+    .map_err(PlaynetError::InvalidWireTransaction)?;
+
+    deserialize_tx(&wire_tx)
+}
+
+/// Deserializes a wire format transaction, it must fit in a packet
+pub fn deserialize_tx(wire_tx: &[u8]) -> Result<VersionedTransaction> {
+    if wire_tx.len() > PACKET_DATA_SIZE {
+        return Err(PlaynetError::InvalidWireTransaction(format!(
+            "decoded {} too large: {} bytes (max: {} bytes)",
+            type_name::<VersionedTransaction>(),
+            wire_tx.len(),
+            PACKET_DATA_SIZE
+        )));
+    }
+
+    bincode::options()
+        .with_limit(PACKET_DATA_SIZE as u64)
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .deserialize_from(wire_tx)
+        .map_err(|err| {
+            PlaynetError::InvalidWireTransaction(format!(
+                "failed to deserialize {}: {}",
+                type_name::<VersionedTransaction>(),
+                err
+            ))
+        })
+}
 
-/// Tries to convert a serialized transaction into `SanitizedTransaction`
-pub fn get_sanitized_tx(serialized_tx: &[u8]) -> transaction::Result<SanitizedTransaction> {
-    let tx: Transaction = serde_json::from_slice(serialized_tx).unwrap();
-    let tx = VersionedTransaction::from(tx);
-    SanitizedTransaction::try_create(
+/// Tries to convert a versioned transaction into `SanitizedTransaction`.
+///
+/// The addresses of the v0 transactions get loaded with `address_loader`.
+pub fn sanitize_tx(
+    tx: VersionedTransaction,
+    address_loader: impl AddressLoader,
+) -> Result<SanitizedTransaction> {
+    let sanitized_tx = SanitizedTransaction::try_create(
         tx,
         MessageHash::Compute,
         Some(false), // is_simple_vote_tx
-        PgAddressLoader::default(),
+        address_loader,
         true, // require_static_program_ids
-    )
+    )?;
+    Ok(sanitized_tx)
 }
 
 /// Create a blockhash from the given bytes
@@ -24,3 +103,15 @@ pub fn create_blockhash(bytes: &[u8]) -> Hash {
     hasher.hash(bytes);
     hasher.result()
 }
+
+/// Parse a base58 encoded public key
+pub fn parse_pubkey(pubkey_str: &str) -> Result<Pubkey> {
+    Pubkey::from_str(pubkey_str)
+        .map_err(|err| PlaynetError::InvalidPubkey(pubkey_str.to_owned(), err))
+}
+
+/// Parse a base58 encoded signature
+pub fn parse_signature(signature_str: &str) -> Result<Signature> {
+    Signature::from_str(signature_str)
+        .map_err(|err| PlaynetError::InvalidSignature(signature_str.to_owned(), err))
+}
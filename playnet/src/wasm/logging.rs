@@ -0,0 +1,74 @@
+// Runtime logs are emitted with `tracing` and are silent unless a subscriber is
+// installed. Native hosts install their own subscriber while WASM hosts route
+// the logs to the browser console with `init_console_logging`.
+
+use std::{io, str::FromStr};
+
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{PlaynetError, Result};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    fn console_info(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(s: &str);
+}
+
+/// Installs a global subscriber that writes the runtime logs up to `max_level`
+/// (`error`, `warn`, `info`, `debug` or `trace`) to the browser console.
+pub fn init_console_logging(max_level: &str) -> Result<()> {
+    let max_level = Level::from_str(max_level)
+        .map_err(|_| PlaynetError::InvalidParam(format!("Invalid log level: {}", max_level)))?;
+
+    tracing_subscriber::fmt()
+        .with_max_level(max_level)
+        .with_writer(MakeConsoleWriter)
+        .with_ansi(false)
+        .without_time()
+        .try_init()
+        .map_err(|_| PlaynetError::InvalidParam("Logging is already enabled".into()))
+}
+
+struct MakeConsoleWriter;
+
+impl<'a> MakeWriter<'a> for MakeConsoleWriter {
+    type Writer = ConsoleWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        ConsoleWriter(Level::INFO)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        ConsoleWriter(*meta.level())
+    }
+}
+
+/// Writes each formatted event to the console method of its level
+struct ConsoleWriter(Level);
+
+impl io::Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let log = String::from_utf8_lossy(buf);
+        let log = log.trim_end();
+        match self.0 {
+            Level::ERROR => console_error(log),
+            Level::WARN => console_warn(log),
+            Level::INFO => console_info(log),
+            _ => console_debug(log),
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
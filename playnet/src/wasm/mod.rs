@@ -0,0 +1,20 @@
+// JS bindings for Playnet, enabled with the `wasm` feature.
+//
+// The bindings are thin wrappers around the core types that convert the
+// arguments and the results to and from values JS can work with.
+
+mod logging;
+mod playnet;
+mod pubsub;
+mod rpc;
+mod types;
+
+use wasm_bindgen::{JsError, JsValue};
+
+use crate::error::PlaynetError;
+
+impl From<PlaynetError> for JsValue {
+    fn from(err: PlaynetError) -> Self {
+        JsError::from(err).into()
+    }
+}
@@ -0,0 +1,171 @@
+// Playnet creates a minimal custom runtime to allow testing Solana programs
+// in browsers(though not limited to) without restrictions with the help of WASM.
+//
+// Playnet is not `solana-test-validator`, it's specifically designed for single
+// user in mind to consume as little resources as possible.
+
+use std::sync::{Arc, RwLock};
+
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    error::Result,
+    pubsub::{LogsFilter, SubscriptionId, SubscriptionParams},
+    rpc::PgRpc,
+    runtime::{
+        bank::{PgBank, SharedBank},
+        snapshot::{read_snapshot, write_snapshot, SnapshotCompression},
+    },
+    utils::{parse_pubkey, parse_signature},
+};
+
+use super::{
+    logging::init_console_logging,
+    rpc::WasmRpc,
+    types::{commitment_or_finalized, WasmCommitmentLevel},
+};
+
+#[wasm_bindgen]
+pub struct Playnet {
+    /// RPC methods to interact with the Playnet
+    #[wasm_bindgen(getter_with_clone)]
+    pub rpc: WasmRpc,
+
+    /// Reference to the bank
+    bank: SharedBank,
+}
+
+#[wasm_bindgen]
+impl Playnet {
+    /// Playnet lifecycle starts after constructing a Playnet instance
+    #[wasm_bindgen(constructor)]
+    pub fn new(maybe_bank_string: Option<String>) -> Result<Playnet> {
+        // Get WASM errors in console
+        console_error_panic_hook::set_once();
+
+        // Create the bank
+        Ok(Self::with_bank(PgBank::new(maybe_bank_string)?))
+    }
+
+    /// Create a Playnet instance from a snapshot created with `getSnapshot`
+    #[wasm_bindgen(js_name = fromSnapshot)]
+    pub fn from_snapshot(snapshot: &[u8]) -> Result<Playnet> {
+        // Get WASM errors in console
+        console_error_panic_hook::set_once();
+
+        Ok(Self::with_bank(read_snapshot(snapshot)?))
+    }
+
+    /// Route the runtime logs up to the given level(`error`, `warn`, `info`,
+    /// `debug` or `trace`) to the browser console. Logging is disabled by default.
+    #[wasm_bindgen(js_name = enableLogging)]
+    pub fn enable_logging(max_level: &str) -> Result<()> {
+        init_console_logging(max_level)
+    }
+
+    /// Get the save data necessary to recover from the next time Playnet instance gets created
+    #[wasm_bindgen(js_name = getSaveData)]
+    pub fn get_save_data(&self) -> Result<String> {
+        Ok(serde_json::to_string(&*self.bank.read().unwrap())?)
+    }
+
+    /// Get the binary snapshot of the bank, compressed if `zstd` feature is enabled
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<Vec<u8>> {
+        let compression = if cfg!(feature = "zstd") {
+            SnapshotCompression::Zstd
+        } else {
+            SnapshotCompression::None
+        };
+
+        let mut snapshot = vec![];
+        write_snapshot(&self.bank.read().unwrap(), &mut snapshot, compression)?;
+        Ok(snapshot)
+    }
+
+    /// Call `callback(account, slot)` when the account changes
+    #[wasm_bindgen(js_name = onAccountChange)]
+    pub fn on_account_change(
+        &self,
+        pubkey_str: &str,
+        callback: Function,
+    ) -> Result<SubscriptionId> {
+        let pubkey = parse_pubkey(pubkey_str)?;
+        Ok(self
+            .rpc
+            .subscribe(SubscriptionParams::Account(pubkey), callback))
+    }
+
+    /// Call `callback(pubkey, account, slot)` when an account owned by the program changes
+    #[wasm_bindgen(js_name = onProgramAccountChange)]
+    pub fn on_program_account_change(
+        &self,
+        program_id_str: &str,
+        callback: Function,
+    ) -> Result<SubscriptionId> {
+        let program_id = parse_pubkey(program_id_str)?;
+        Ok(self.rpc.subscribe(
+            SubscriptionParams::Program {
+                program_id,
+                filters: vec![],
+            },
+            callback,
+        ))
+    }
+
+    /// Call `callback(error, slot)` once the transaction is committed and its
+    /// slot reaches the commitment level, `finalized` by default
+    #[wasm_bindgen(js_name = onSignature)]
+    pub fn on_signature(
+        &self,
+        signature_str: &str,
+        callback: Function,
+        commitment: Option<WasmCommitmentLevel>,
+    ) -> Result<SubscriptionId> {
+        let signature = parse_signature(signature_str)?;
+        Ok(self.rpc.subscribe(
+            SubscriptionParams::Signature {
+                signature,
+                commitment: commitment_or_finalized(commitment),
+            },
+            callback,
+        ))
+    }
+
+    /// Call `callback(signature, error, logs, slot)` when a transaction that
+    /// mentions the account, or any transaction if not specified, is committed
+    #[wasm_bindgen(js_name = onLogs)]
+    pub fn on_logs(&self, mentions: Option<String>, callback: Function) -> Result<SubscriptionId> {
+        let filter = match mentions {
+            Some(pubkey_str) => LogsFilter::Mentions(parse_pubkey(&pubkey_str)?),
+            None => LogsFilter::All,
+        };
+        Ok(self
+            .rpc
+            .subscribe(SubscriptionParams::Logs(filter), callback))
+    }
+
+    /// Call `callback(slot, parent, root)` when the Playnet moves to a new slot
+    #[wasm_bindgen(js_name = onSlotChange)]
+    pub fn on_slot_change(&self, callback: Function) -> SubscriptionId {
+        self.rpc.subscribe(SubscriptionParams::Slot, callback)
+    }
+
+    /// Returns `false` if the subscription doesn't exist
+    #[wasm_bindgen(js_name = removeSubscription)]
+    pub fn remove_subscription(&self, id: SubscriptionId) -> bool {
+        self.rpc.unsubscribe(id)
+    }
+}
+
+impl Playnet {
+    fn with_bank(bank: PgBank) -> Self {
+        let bank = Arc::new(RwLock::new(bank));
+
+        Self {
+            rpc: WasmRpc::new(PgRpc::new(Arc::clone(&bank))),
+            bank: Arc::clone(&bank),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// Delivers the subscription notifications to JS callbacks.
+//
+// Notifications are queued while the bank is locked and the callbacks get
+// called after the bank is unlocked, so the callbacks are free to use the RPC.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    mem,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use js_sys::{Array, Function};
+use tracing::warn;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    pubsub::{Notification, NotificationSink, SubscriptionId, SubscriptionParams},
+    rpc::PgRpc,
+};
+
+use super::types::WasmAccount;
+
+#[derive(Clone, Default)]
+pub struct WasmPubSub {
+    queue: Arc<Mutex<Vec<(SubscriptionId, Notification)>>>,
+    callbacks: Rc<RefCell<HashMap<SubscriptionId, Function>>>,
+}
+
+impl WasmPubSub {
+    pub fn subscribe(
+        &self,
+        rpc: &PgRpc,
+        params: SubscriptionParams,
+        callback: Function,
+    ) -> SubscriptionId {
+        let id = rpc.subscribe(params, self.sink());
+        self.callbacks.borrow_mut().insert(id, callback);
+
+        // Committed transactions that already reached the commitment level get
+        // notified immediately
+        self.flush();
+        id
+    }
+
+    pub fn unsubscribe(&self, rpc: &PgRpc, id: SubscriptionId) -> bool {
+        self.callbacks.borrow_mut().remove(&id);
+        rpc.unsubscribe(id)
+    }
+
+    /// Calls the callbacks of the queued notifications
+    pub fn flush(&self) {
+        let notifications = mem::take(&mut *self.queue.lock().unwrap());
+        for (id, notification) in notifications {
+            let callback = match self.callbacks.borrow().get(&id) {
+                Some(callback) => callback.clone(),
+                None => continue,
+            };
+
+            let args: Vec<JsValue> = match notification {
+                Notification::Account { account, slot } => {
+                    vec![WasmAccount::from(account).into(), slot.into()]
+                }
+                Notification::Program {
+                    pubkey,
+                    account,
+                    slot,
+                } => vec![
+                    pubkey.to_string().into(),
+                    WasmAccount::from(account).into(),
+                    slot.into(),
+                ],
+                Notification::Signature { err, slot } => {
+                    // Signature subscriptions end after the first notification
+                    self.callbacks.borrow_mut().remove(&id);
+                    vec![err.map(|err| err.to_string()).into(), slot.into()]
+                }
+                Notification::Logs {
+                    signature,
+                    err,
+                    logs,
+                    slot,
+                } => vec![
+                    signature.to_string().into(),
+                    err.map(|err| err.to_string()).into(),
+                    logs.into_iter()
+                        .map(JsValue::from)
+                        .collect::<Array>()
+                        .into(),
+                    slot.into(),
+                ],
+                Notification::Slot { slot, parent, root } => {
+                    vec![slot.into(), parent.into(), root.into()]
+                }
+            };
+
+            let args = args.into_iter().collect::<Array>();
+            if let Err(err) = callback.apply(&JsValue::NULL, &args) {
+                warn!(id, ?err, "Subscription callback failed");
+            }
+        }
+    }
+
+    fn sink(&self) -> NotificationSink {
+        let queue = Arc::clone(&self.queue);
+        Box::new(move |id, notification| queue.lock().unwrap().push((id, notification)))
+    }
+}
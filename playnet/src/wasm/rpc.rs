@@ -0,0 +1,454 @@
+use std::time::Duration;
+
+use js_sys::Function;
+use solana_sdk::{
+    clock::UnixTimestamp,
+    message::{Message, VersionedMessage},
+    slot_history::Slot,
+    transaction::SanitizedTransaction,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    error::{PlaynetError, Result},
+    pubsub::{SubscriptionId, SubscriptionParams},
+    rpc::PgRpc,
+    runtime::{bpf_tracer::BpfTracing, commitment::CommitmentConfig, faucet::AirdropLimits},
+    types::{AccountFilter, LogLimit, SimulateTransactionConfig, TransactionBinaryEncoding},
+    utils::{decode_tx, parse_pubkey, parse_signature},
+};
+
+use super::{
+    pubsub::WasmPubSub,
+    types::{
+        commitment_or_finalized, GetBlockResult, GetLatestBlockhashResult,
+        GetSignatureStatusesResult, GetTransactionResult, SendTransactionResult, WasmAccount,
+        WasmCommitmentLevel, WasmEpochInfo, WasmEpochSchedule, WasmInflationRate, WasmKeyedAccount,
+        WasmSignatureInfo, WasmSimulateTransactionConfig, WasmSimulateTransactionResult,
+        WasmSupply,
+    },
+};
+
+/// JS facing wrapper of `PgRpc`
+#[wasm_bindgen(js_name = PgRpc)]
+#[derive(Clone)]
+pub struct WasmRpc {
+    rpc: PgRpc,
+    pubsub: WasmPubSub,
+}
+
+impl WasmRpc {
+    pub fn new(rpc: PgRpc) -> Self {
+        Self {
+            rpc,
+            pubsub: WasmPubSub::default(),
+        }
+    }
+
+    pub fn subscribe(&self, params: SubscriptionParams, callback: Function) -> SubscriptionId {
+        self.pubsub.subscribe(&self.rpc, params, callback)
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.pubsub.unsubscribe(&self.rpc, id)
+    }
+
+    /// Decodes and sanitizes a wire format transaction
+    fn get_sanitized_tx(
+        &self,
+        encoded_tx: &str,
+        encoding: Option<String>,
+    ) -> Result<SanitizedTransaction> {
+        let encoding = match encoding {
+            Some(encoding) => encoding.parse()?,
+            None => TransactionBinaryEncoding::Base58,
+        };
+        self.rpc
+            .sanitize_transaction(decode_tx(encoded_tx, encoding)?)
+    }
+}
+
+#[wasm_bindgen(js_class = PgRpc)]
+impl WasmRpc {
+    #[wasm_bindgen(js_name = getAccountInfo)]
+    pub fn get_account_info(
+        &self,
+        pubkey_str: &str,
+        commitment: Option<WasmCommitmentLevel>,
+    ) -> Result<WasmAccount> {
+        let pubkey = parse_pubkey(pubkey_str)?;
+        Ok(WasmAccount::from(
+            self.rpc
+                .get_account_info_with_commitment(&pubkey, commitment_or_finalized(commitment))
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Returns the `{ program, parsed, space }` JSON of the account data,
+    /// `undefined` if the account doesn't exist or can't be decoded
+    #[wasm_bindgen(js_name = getParsedAccountInfo)]
+    pub fn get_parsed_account_info(&self, pubkey_str: &str) -> Result<Option<String>> {
+        let pubkey = parse_pubkey(pubkey_str)?;
+        let parsed = self
+            .rpc
+            .get_account_info(&pubkey)
+            .and_then(|account| self.rpc.decode_account(&pubkey, &account));
+        Ok(match parsed {
+            Some(parsed) => Some(serde_json::to_string(&parsed)?),
+            None => None,
+        })
+    }
+
+    #[wasm_bindgen(js_name = getMultipleAccounts)]
+    pub fn get_multiple_accounts(
+        &self,
+        pubkeys: Vec<JsValue>,
+        commitment: Option<WasmCommitmentLevel>,
+    ) -> Result<Vec<JsValue>> {
+        let pubkeys = pubkeys
+            .iter()
+            .map(|js_pubkey| {
+                js_pubkey
+                    .as_string()
+                    .ok_or_else(|| PlaynetError::InvalidParam("Public key must be a string".into()))
+                    .and_then(|pubkey_str| parse_pubkey(&pubkey_str))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self
+            .rpc
+            .get_multiple_accounts_with_commitment(&pubkeys, commitment_or_finalized(commitment))
+            .into_iter()
+            .map(|account| JsValue::from(account.map(WasmAccount::from)))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getBalance)]
+    pub fn get_balance(
+        &self,
+        pubkey_str: &str,
+        commitment: Option<WasmCommitmentLevel>,
+    ) -> Result<u64> {
+        let pubkey = parse_pubkey(pubkey_str)?;
+        Ok(self
+            .rpc
+            .get_balance_with_commitment(&pubkey, commitment_or_finalized(commitment)))
+    }
+
+    /// Accounts must match all the given filters
+    #[wasm_bindgen(js_name = getProgramAccounts)]
+    pub fn get_program_accounts(
+        &self,
+        program_id_str: &str,
+        data_size: Option<u64>,
+        memcmp_offset: Option<usize>,
+        memcmp_bytes: Option<Vec<u8>>,
+    ) -> Result<Vec<JsValue>> {
+        let program_id = parse_pubkey(program_id_str)?;
+        let mut filters = vec![];
+        if let Some(size) = data_size {
+            filters.push(AccountFilter::DataSize(size));
+        }
+        match (memcmp_offset, memcmp_bytes) {
+            (Some(offset), Some(bytes)) => filters.push(AccountFilter::Memcmp { offset, bytes }),
+            (None, None) => {}
+            _ => {
+                return Err(PlaynetError::InvalidParam(
+                    "Memcmp filter requires both the offset and the bytes".into(),
+                ))
+            }
+        }
+
+        Ok(self
+            .rpc
+            .get_program_accounts(&program_id, &filters)
+            .into_iter()
+            .map(|keyed_account| JsValue::from(WasmKeyedAccount::from(keyed_account)))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getSlot)]
+    pub fn get_slot(&self, commitment: Option<WasmCommitmentLevel>) -> Slot {
+        self.rpc
+            .get_slot_with_commitment(commitment_or_finalized(commitment))
+    }
+
+    #[wasm_bindgen(js_name = getBlockHeight)]
+    pub fn get_block_height(&self, commitment: Option<WasmCommitmentLevel>) -> u64 {
+        self.rpc
+            .get_block_height_with_commitment(commitment_or_finalized(commitment))
+    }
+
+    /// Slots get confirmed and finalized after the given number of slots are
+    /// built on top of them, and at least `latency_ms` after the previous
+    /// commitment level
+    #[wasm_bindgen(js_name = setCommitmentConfig)]
+    pub fn set_commitment_config(
+        &self,
+        confirmation_depth: u64,
+        finalization_depth: u64,
+        latency_ms: Option<u64>,
+    ) {
+        self.rpc.set_commitment_config(CommitmentConfig {
+            confirmation_depth,
+            finalization_depth,
+            latency: latency_ms.map(Duration::from_millis),
+        });
+    }
+
+    /// `mode` is `per-transaction`(default), `batch:<transactions>`,
+    /// `interval:<ms>` or `manual`. The interval mode only stops transactions
+    /// from ending the slots, call `advanceSlots` on a timer to end them.
+    #[wasm_bindgen(js_name = setBlockProduction)]
+    pub fn set_block_production(&self, mode: &str) -> Result<()> {
+        self.rpc.set_block_production(mode.parse()?);
+        Ok(())
+    }
+
+    /// Ends the given number of slots and returns the new slot
+    #[wasm_bindgen(js_name = advanceSlots)]
+    pub fn advance_slots(&self, count: u64) -> Slot {
+        let slot = self.rpc.advance_slots(count);
+        self.pubsub.flush();
+        slot
+    }
+
+    /// Logs of the transactions get truncated after `bytes`, `undefined`
+    /// keeps all the logs
+    #[wasm_bindgen(js_name = setLogBytesLimit)]
+    pub fn set_log_bytes_limit(&self, bytes: Option<usize>) {
+        self.rpc
+            .set_log_limit(bytes.map(LogLimit::Bytes).unwrap_or(LogLimit::Unlimited));
+    }
+
+    /// Traces the instructions that BPF programs execute, the traces are in
+    /// the invocations of the simulation results
+    #[wasm_bindgen(js_name = setBpfTracing)]
+    pub fn set_bpf_tracing(&self, enabled: bool) {
+        self.rpc.set_bpf_tracing(if enabled {
+            BpfTracing::Enabled
+        } else {
+            BpfTracing::Disabled
+        });
+    }
+
+    #[wasm_bindgen(js_name = getGenesisHash)]
+    pub fn get_genesis_hash(&self) -> String {
+        self.rpc.get_genesis_hash().to_string()
+    }
+
+    #[wasm_bindgen(js_name = getLatestBlockhash)]
+    pub fn get_latest_blockhash(&self) -> GetLatestBlockhashResult {
+        GetLatestBlockhashResult::from(self.rpc.get_latest_blockhash())
+    }
+
+    #[wasm_bindgen(js_name = getEpochInfo)]
+    pub fn get_epoch_info(&self) -> WasmEpochInfo {
+        WasmEpochInfo::from(self.rpc.get_epoch_info())
+    }
+
+    #[wasm_bindgen(js_name = getEpochSchedule)]
+    pub fn get_epoch_schedule(&self) -> WasmEpochSchedule {
+        WasmEpochSchedule::from(self.rpc.get_epoch_schedule())
+    }
+
+    #[wasm_bindgen(js_name = getSupply)]
+    pub fn get_supply(&self) -> WasmSupply {
+        WasmSupply::from(self.rpc.get_supply())
+    }
+
+    #[wasm_bindgen(js_name = getInflationRate)]
+    pub fn get_inflation_rate(&self) -> WasmInflationRate {
+        WasmInflationRate::from(self.rpc.get_inflation_rate())
+    }
+
+    #[wasm_bindgen(js_name = getIdentity)]
+    pub fn get_identity(&self) -> String {
+        self.rpc.get_identity().to_string()
+    }
+
+    #[wasm_bindgen(js_name = getMinimumBalanceForRentExemption)]
+    pub fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> u64 {
+        self.rpc.get_minimum_balance_for_rent_exemption(data_len)
+    }
+
+    #[wasm_bindgen(js_name = getFeeForMessage)]
+    pub fn get_fee_for_message(&self, serialized_msg: &[u8]) -> Result<Option<u64>> {
+        let msg: Message = serde_json::from_slice(serialized_msg)?;
+        self.rpc.get_fee_for_message(VersionedMessage::Legacy(msg))
+    }
+
+    /// `encoding` is either `base58`(default) or `base64`
+    #[wasm_bindgen(js_name = simulateTransaction)]
+    pub fn simulate_transaction(
+        &self,
+        encoded_tx: &str,
+        encoding: Option<String>,
+        config: Option<WasmSimulateTransactionConfig>,
+    ) -> Result<WasmSimulateTransactionResult> {
+        let encoding = match encoding {
+            Some(encoding) => encoding.parse()?,
+            None => TransactionBinaryEncoding::Base58,
+        };
+        let config = config
+            .map(SimulateTransactionConfig::from)
+            .unwrap_or_default();
+        match self
+            .rpc
+            .simulate_transaction_with_config(decode_tx(encoded_tx, encoding)?, &config)
+        {
+            Ok(result) => Ok(result.into()),
+            Err(PlaynetError::Transaction(err)) => {
+                Ok(WasmSimulateTransactionResult::new_error(err))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// `encoding` is either `base58`(default) or `base64`
+    #[wasm_bindgen(js_name = sendTransaction)]
+    pub fn send_transaction(
+        &self,
+        encoded_tx: &str,
+        encoding: Option<String>,
+    ) -> Result<SendTransactionResult> {
+        let sanitized_tx = match self.get_sanitized_tx(encoded_tx, encoding) {
+            Ok(sanitized_tx) => sanitized_tx,
+            Err(PlaynetError::Transaction(err)) => {
+                return Ok(SendTransactionResult::new_error(err))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let result = match self.rpc.send_transaction(sanitized_tx) {
+            Ok(signature) => SendTransactionResult::new(signature.to_string()),
+            Err(PlaynetError::Transaction(err))
+            | Err(PlaynetError::PrecompileVerificationFailure(err)) => {
+                SendTransactionResult::new_error(err)
+            }
+            Err(err) => return Err(err),
+        };
+        self.pubsub.flush();
+
+        Ok(result)
+    }
+
+    /// Only the recent transactions are found unless `searchTransactionHistory`
+    /// is set
+    #[wasm_bindgen(js_name = getSignatureStatuses)]
+    pub fn get_signature_statuses(
+        &self,
+        signatures: Vec<JsValue>,
+        search_transaction_history: Option<bool>,
+    ) -> Result<GetSignatureStatusesResult> {
+        let signatures = signatures
+            .iter()
+            .map(|js_signature| {
+                js_signature
+                    .as_string()
+                    .ok_or_else(|| PlaynetError::InvalidParam("Signature must be a string".into()))
+                    .and_then(|signature_str| parse_signature(&signature_str))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(GetSignatureStatusesResult::new(
+            self.rpc
+                .get_signature_statuses(&signatures, search_transaction_history.unwrap_or(false)),
+        ))
+    }
+
+    #[wasm_bindgen(js_name = getTransaction)]
+    pub fn get_transaction(&self, signature_str: &str) -> Result<GetTransactionResult> {
+        let signature = parse_signature(signature_str)?;
+        Ok(GetTransactionResult::new(
+            self.rpc.get_transaction(&signature),
+        ))
+    }
+
+    /// Returns at most `limit` (default 1000) transactions, newest first
+    #[wasm_bindgen(js_name = getSignaturesForAddress)]
+    pub fn get_signatures_for_address(
+        &self,
+        address_str: &str,
+        before_str: Option<String>,
+        until_str: Option<String>,
+        limit: Option<usize>,
+        commitment: Option<WasmCommitmentLevel>,
+    ) -> Result<Vec<JsValue>> {
+        let address = parse_pubkey(address_str)?;
+        let before = before_str.as_deref().map(parse_signature).transpose()?;
+        let until = until_str.as_deref().map(parse_signature).transpose()?;
+
+        Ok(self
+            .rpc
+            .get_signatures_for_address(
+                &address,
+                before.as_ref(),
+                until.as_ref(),
+                limit.unwrap_or(1000),
+                commitment_or_finalized(commitment),
+            )
+            .into_iter()
+            .map(|info| JsValue::from(WasmSignatureInfo::from(info)))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getBlock)]
+    pub fn get_block(&self, slot: Slot) -> GetBlockResult {
+        GetBlockResult::new(self.rpc.get_block(slot))
+    }
+
+    /// `end_slot` defaults to the current slot
+    #[wasm_bindgen(js_name = getBlocks)]
+    pub fn get_blocks(&self, start_slot: Slot, end_slot: Option<Slot>) -> Vec<Slot> {
+        self.rpc.get_blocks(start_slot, end_slot)
+    }
+
+    #[wasm_bindgen(js_name = getBlocksWithLimit)]
+    pub fn get_blocks_with_limit(&self, start_slot: Slot, limit: usize) -> Vec<Slot> {
+        self.rpc.get_blocks_with_limit(start_slot, limit)
+    }
+
+    #[wasm_bindgen(js_name = getBlockTime)]
+    pub fn get_block_time(&self, slot: Slot) -> Result<UnixTimestamp> {
+        self.rpc
+            .get_block_time(slot)
+            .ok_or(PlaynetError::BlockNotFound)
+    }
+
+    #[wasm_bindgen(js_name = getFirstAvailableBlock)]
+    pub fn get_first_available_block(&self) -> Slot {
+        self.rpc.get_first_available_block()
+    }
+
+    #[wasm_bindgen(js_name = requestAirdrop)]
+    pub fn request_airdrop(
+        &self,
+        pubkey_str: &str,
+        lamports: u64,
+    ) -> Result<SendTransactionResult> {
+        let pubkey = parse_pubkey(pubkey_str)?;
+        let result = match self.rpc.request_airdrop(&pubkey, lamports) {
+            Ok(signature) => SendTransactionResult::new(signature.to_string()),
+            Err(PlaynetError::Transaction(err)) => SendTransactionResult::new_error(err),
+            Err(err) => return Err(err),
+        };
+        self.pubsub.flush();
+
+        Ok(result)
+    }
+
+    #[wasm_bindgen(js_name = getFaucetPubkey)]
+    pub fn get_faucet_pubkey(&self) -> String {
+        self.rpc.get_faucet_pubkey().to_string()
+    }
+
+    /// Caps are in lamports, `undefined` caps are unlimited
+    #[wasm_bindgen(js_name = setAirdropLimits)]
+    pub fn set_airdrop_limits(&self, per_request_cap: Option<u64>, per_account_cap: Option<u64>) {
+        self.rpc.set_airdrop_limits(AirdropLimits {
+            per_request_cap,
+            per_account_cap,
+        });
+    }
+}
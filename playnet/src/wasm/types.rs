@@ -0,0 +1,1006 @@
+use solana_sdk::{
+    account::Account,
+    clock::UnixTimestamp,
+    epoch_info::EpochInfo,
+    epoch_schedule::EpochSchedule,
+    hash::Hash,
+    instruction::CompiledInstruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    slot_history::Slot,
+    stake_history::Epoch,
+    transaction::{TransactionError, TransactionVersion},
+    transaction_context::TransactionReturnData,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    error::{PlaynetError, Result},
+    runtime::{
+        invocation::{ExecutionTimings, Invocation},
+        transaction_history::{
+            CompiledInnerInstruction, ConfirmedTransactionMeta, InstructionComputeUnits,
+            ProgramExecutionDetails, TransactionData,
+        },
+    },
+    types::{
+        AccountOverride, CommitmentLevel, ConfirmedBlock, InflationRate, LatestBlockhash, LogLimit,
+        SignatureInfo, SimulateTransactionConfig, SimulateTransactionResult, Supply,
+        TransactionStatus,
+    },
+    utils::parse_pubkey,
+};
+
+#[wasm_bindgen]
+pub struct WasmAccount {
+    /// Lamports in the account
+    pub lamports: u64,
+    /// Data held in this account
+    #[wasm_bindgen(getter_with_clone)]
+    pub data: Vec<u8>,
+    /// The program that owns this account. If executable, the program that loads this account.
+    pub owner: Pubkey,
+    /// This account's data contains a loaded program (and is now read-only)
+    pub executable: bool,
+    /// The epoch at which this account will next owe rent
+    #[wasm_bindgen(js_name = rentEpoch)]
+    pub rent_epoch: Epoch,
+}
+
+impl From<Account> for WasmAccount {
+    fn from(account: Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            data: account.data,
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = KeyedAccount)]
+pub struct WasmKeyedAccount {
+    pubkey: Pubkey,
+    account: Account,
+}
+
+impl From<(Pubkey, Account)> for WasmKeyedAccount {
+    fn from((pubkey, account): (Pubkey, Account)) -> Self {
+        Self { pubkey, account }
+    }
+}
+
+#[wasm_bindgen(js_class = KeyedAccount)]
+impl WasmKeyedAccount {
+    pub fn pubkey(&self) -> String {
+        self.pubkey.to_string()
+    }
+
+    pub fn account(&self) -> WasmAccount {
+        WasmAccount::from(self.account.clone())
+    }
+}
+
+#[wasm_bindgen]
+pub struct GetLatestBlockhashResult {
+    blockhash: Hash,
+    last_valid_block_height: u64,
+}
+
+impl From<LatestBlockhash> for GetLatestBlockhashResult {
+    fn from(val: LatestBlockhash) -> Self {
+        Self {
+            blockhash: val.blockhash,
+            last_valid_block_height: val.last_valid_block_height,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl GetLatestBlockhashResult {
+    pub fn blockhash(&self) -> String {
+        self.blockhash.to_string()
+    }
+
+    #[wasm_bindgen(js_name = lastValidBlockHeight)]
+    pub fn last_valid_block_height(&self) -> u64 {
+        self.last_valid_block_height
+    }
+}
+
+/// Return data at the end of a transaction
+#[wasm_bindgen]
+pub struct WasmTransactionReturnData {
+    #[wasm_bindgen(js_name = programId)]
+    pub program_id: Pubkey,
+    #[wasm_bindgen(getter_with_clone)]
+    pub data: Vec<u8>,
+}
+
+impl From<TransactionReturnData> for WasmTransactionReturnData {
+    fn from(val: TransactionReturnData) -> Self {
+        Self {
+            data: val.data,
+            program_id: val.program_id,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = SimulateTransactionResult)]
+pub struct WasmSimulateTransactionResult {
+    inner: SimulateTransactionResult,
+}
+
+impl WasmSimulateTransactionResult {
+    pub fn new_error(err: TransactionError) -> Self {
+        SimulateTransactionResult::new_error(err).into()
+    }
+}
+
+impl From<SimulateTransactionResult> for WasmSimulateTransactionResult {
+    fn from(inner: SimulateTransactionResult) -> Self {
+        Self { inner }
+    }
+}
+
+#[wasm_bindgen(js_class = SimulateTransactionResult)]
+impl WasmSimulateTransactionResult {
+    pub fn error(&self) -> Option<String> {
+        match &self.inner.result {
+            Ok(_) => None,
+            Err(err) => Some(err.to_string()),
+        }
+    }
+
+    pub fn logs(&self) -> Vec<JsValue> {
+        self.inner
+            .logs
+            .iter()
+            .map(|log| JsValue::from_str(log))
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = unitsConsumed)]
+    pub fn units_consumed(&self) -> u64 {
+        self.inner.units_consumed
+    }
+
+    #[wasm_bindgen(js_name = returnData)]
+    pub fn return_data(&self) -> Option<WasmTransactionReturnData> {
+        self.inner
+            .return_data
+            .as_ref()
+            .map(|tx_return_data| WasmTransactionReturnData::from(tx_return_data.to_owned()))
+    }
+
+    /// Requested accounts after the simulation, `undefined` for the accounts
+    /// that don't exist
+    pub fn accounts(&self) -> Option<Vec<JsValue>> {
+        self.inner.accounts.as_ref().map(|accounts| {
+            accounts
+                .iter()
+                .map(|account| match account {
+                    Some(account) => WasmAccount::from(account.to_owned()).into(),
+                    None => JsValue::UNDEFINED,
+                })
+                .collect()
+        })
+    }
+
+    #[wasm_bindgen(js_name = innerInstructions)]
+    pub fn inner_instructions(&self) -> Option<Vec<JsValue>> {
+        self.inner
+            .inner_instructions
+            .as_deref()
+            .map(inner_instructions_to_js)
+    }
+
+    #[wasm_bindgen(js_name = replacementBlockhash)]
+    pub fn replacement_blockhash(&self) -> Option<GetLatestBlockhashResult> {
+        self.inner.replacement_blockhash.map(|latest| latest.into())
+    }
+
+    /// Invocation trees of the executed instructions
+    pub fn invocations(&self) -> Vec<JsValue> {
+        invocations_to_js(&self.inner.invocations)
+    }
+
+    /// Compute units consumed by each instruction and its cross program
+    /// invocations
+    #[wasm_bindgen(js_name = instructionComputeUnits)]
+    pub fn instruction_compute_units(&self) -> Vec<JsValue> {
+        instruction_compute_units_to_js(&self.inner.instruction_compute_units())
+    }
+
+    /// Execution details of the programs of the transaction instructions
+    #[wasm_bindgen(js_name = programDetails)]
+    pub fn program_details(&self) -> Vec<JsValue> {
+        program_details_to_js(&self.inner.program_details)
+    }
+}
+
+#[wasm_bindgen(js_name = Invocation)]
+pub struct WasmInvocation {
+    inner: Invocation,
+}
+
+#[wasm_bindgen(js_class = Invocation)]
+impl WasmInvocation {
+    #[wasm_bindgen(js_name = programId)]
+    pub fn program_id(&self) -> String {
+        self.inner.program_id.to_string()
+    }
+
+    /// 1 for the instructions of the transaction
+    pub fn depth(&self) -> usize {
+        self.inner.depth
+    }
+
+    /// Includes the units of the inner invocations
+    #[wasm_bindgen(js_name = computeUnitsConsumed)]
+    pub fn compute_units_consumed(&self) -> u64 {
+        self.inner.compute_units_consumed
+    }
+
+    /// Logs emitted in this invocation, without the logs of the inner
+    /// invocations
+    pub fn logs(&self) -> Vec<JsValue> {
+        self.inner
+            .logs
+            .iter()
+            .map(|log| JsValue::from_str(log))
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = returnData)]
+    pub fn return_data(&self) -> Option<Vec<u8>> {
+        self.inner.return_data.clone()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.inner.result.as_ref().err().map(|err| err.to_string())
+    }
+
+    /// Includes the timings of the inner invocations
+    pub fn timings(&self) -> WasmExecutionTimings {
+        self.inner.timings.into()
+    }
+
+    /// Executed instructions as disassembly, `undefined` unless BPF tracing is
+    /// enabled
+    pub fn trace(&self) -> Option<String> {
+        self.inner.trace.as_ref().map(|trace| trace.disassemble())
+    }
+
+    #[wasm_bindgen(js_name = innerInvocations)]
+    pub fn inner_invocations(&self) -> Vec<JsValue> {
+        invocations_to_js(&self.inner.inner_invocations)
+    }
+}
+
+fn invocations_to_js(invocations: &[Invocation]) -> Vec<JsValue> {
+    invocations
+        .iter()
+        .map(|invocation| {
+            WasmInvocation {
+                inner: invocation.to_owned(),
+            }
+            .into()
+        })
+        .collect()
+}
+
+/// Time spent in the stages of the BPF loader, in microseconds
+#[wasm_bindgen(js_name = ExecutionTimings)]
+#[derive(Clone, Copy)]
+pub struct WasmExecutionTimings {
+    #[wasm_bindgen(js_name = getOrCreateExecutorUs)]
+    pub get_or_create_executor_us: u64,
+    #[wasm_bindgen(js_name = serializeUs)]
+    pub serialize_us: u64,
+    #[wasm_bindgen(js_name = createVmUs)]
+    pub create_vm_us: u64,
+    #[wasm_bindgen(js_name = executeUs)]
+    pub execute_us: u64,
+    #[wasm_bindgen(js_name = deserializeUs)]
+    pub deserialize_us: u64,
+}
+
+impl From<ExecutionTimings> for WasmExecutionTimings {
+    fn from(timings: ExecutionTimings) -> Self {
+        Self {
+            get_or_create_executor_us: timings.get_or_create_executor_us,
+            serialize_us: timings.serialize_us,
+            create_vm_us: timings.create_vm_us,
+            execute_us: timings.execute_us,
+            deserialize_us: timings.deserialize_us,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = InstructionComputeUnits)]
+pub struct WasmInstructionComputeUnits {
+    inner: InstructionComputeUnits,
+}
+
+#[wasm_bindgen(js_class = InstructionComputeUnits)]
+impl WasmInstructionComputeUnits {
+    #[wasm_bindgen(js_name = programId)]
+    pub fn program_id(&self) -> String {
+        self.inner.program_id.to_string()
+    }
+
+    /// Includes the units of the inner instructions
+    #[wasm_bindgen(js_name = unitsConsumed)]
+    pub fn units_consumed(&self) -> u64 {
+        self.inner.units_consumed
+    }
+
+    #[wasm_bindgen(js_name = innerInstructions)]
+    pub fn inner_instructions(&self) -> Vec<JsValue> {
+        instruction_compute_units_to_js(&self.inner.inner_instructions)
+    }
+}
+
+fn instruction_compute_units_to_js(compute_units: &[InstructionComputeUnits]) -> Vec<JsValue> {
+    compute_units
+        .iter()
+        .map(|compute_units| {
+            WasmInstructionComputeUnits {
+                inner: compute_units.to_owned(),
+            }
+            .into()
+        })
+        .collect()
+}
+
+#[wasm_bindgen(js_name = ProgramExecutionDetails)]
+pub struct WasmProgramExecutionDetails {
+    inner: ProgramExecutionDetails,
+}
+
+#[wasm_bindgen(js_class = ProgramExecutionDetails)]
+impl WasmProgramExecutionDetails {
+    #[wasm_bindgen(js_name = programId)]
+    pub fn program_id(&self) -> String {
+        self.inner.program_id.to_string()
+    }
+
+    /// Number of successful instructions
+    pub fn count(&self) -> u32 {
+        self.inner.count
+    }
+
+    /// Units consumed by the successful instructions
+    #[wasm_bindgen(js_name = unitsConsumed)]
+    pub fn units_consumed(&self) -> u64 {
+        self.inner.units_consumed
+    }
+
+    /// Units consumed by the failed instructions
+    #[wasm_bindgen(js_name = erroredUnits)]
+    pub fn errored_units(&self) -> u64 {
+        self.inner.errored_units
+    }
+
+    pub fn timings(&self) -> WasmExecutionTimings {
+        self.inner.timings.into()
+    }
+}
+
+fn program_details_to_js(program_details: &[ProgramExecutionDetails]) -> Vec<JsValue> {
+    program_details
+        .iter()
+        .map(|details| {
+            WasmProgramExecutionDetails {
+                inner: details.to_owned(),
+            }
+            .into()
+        })
+        .collect()
+}
+
+#[wasm_bindgen(js_name = SimulateTransactionConfig)]
+#[derive(Default)]
+pub struct WasmSimulateTransactionConfig {
+    inner: SimulateTransactionConfig,
+}
+
+impl From<WasmSimulateTransactionConfig> for SimulateTransactionConfig {
+    fn from(config: WasmSimulateTransactionConfig) -> Self {
+        config.inner
+    }
+}
+
+#[wasm_bindgen(js_class = SimulateTransactionConfig)]
+impl WasmSimulateTransactionConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Conflicts with `replaceRecentBlockhash`
+    #[wasm_bindgen(js_name = setSigVerify)]
+    pub fn set_sig_verify(&mut self, sig_verify: bool) {
+        self.inner.sig_verify = sig_verify;
+    }
+
+    #[wasm_bindgen(js_name = setReplaceRecentBlockhash)]
+    pub fn set_replace_recent_blockhash(&mut self, replace_recent_blockhash: bool) {
+        self.inner.replace_recent_blockhash = replace_recent_blockhash;
+    }
+
+    /// Addresses of the accounts to return after the simulation
+    #[wasm_bindgen(js_name = setAccounts)]
+    pub fn set_accounts(&mut self, addresses: Vec<JsValue>) -> Result<()> {
+        let addresses = addresses
+            .iter()
+            .map(|js_pubkey| {
+                js_pubkey
+                    .as_string()
+                    .ok_or_else(|| PlaynetError::InvalidParam("Public key must be a string".into()))
+                    .and_then(|pubkey_str| parse_pubkey(&pubkey_str))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.inner.accounts = Some(addresses);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setInnerInstructions)]
+    pub fn set_inner_instructions(&mut self, inner_instructions: bool) {
+        self.inner.inner_instructions = inner_instructions;
+    }
+
+    #[wasm_bindgen(js_name = setMinContextSlot)]
+    pub fn set_min_context_slot(&mut self, min_context_slot: Slot) {
+        self.inner.min_context_slot = Some(min_context_slot);
+    }
+
+    /// Simulates as if the account had the given fields, `undefined` fields
+    /// are kept as they are
+    #[wasm_bindgen(js_name = setAccountOverride)]
+    pub fn set_account_override(
+        &mut self,
+        address: &str,
+        lamports: Option<u64>,
+        data: Option<Vec<u8>>,
+        owner: Option<String>,
+        executable: Option<bool>,
+    ) -> Result<()> {
+        let owner = match owner {
+            Some(owner) => Some(parse_pubkey(&owner)?),
+            None => None,
+        };
+        self.inner.state_overrides.accounts.insert(
+            parse_pubkey(address)?,
+            AccountOverride {
+                lamports,
+                data,
+                owner,
+                executable,
+            },
+        );
+        Ok(())
+    }
+
+    /// Simulates with the given ELF instead of the deployed program
+    #[wasm_bindgen(js_name = setProgramElf)]
+    pub fn set_program_elf(&mut self, program_id: &str, elf: Vec<u8>) -> Result<()> {
+        self.inner
+            .state_overrides
+            .programs
+            .insert(parse_pubkey(program_id)?, elf);
+        Ok(())
+    }
+
+    /// Simulates with the given log limit instead of the one of the bank,
+    /// `undefined` keeps all the logs
+    #[wasm_bindgen(js_name = setLogBytesLimit)]
+    pub fn set_log_bytes_limit(&mut self, bytes: Option<usize>) {
+        self.inner.log_limit = Some(bytes.map(LogLimit::Bytes).unwrap_or(LogLimit::Unlimited));
+    }
+}
+
+#[wasm_bindgen(js_name = InnerInstructions)]
+pub struct WasmInnerInstructions {
+    /// Index of the transaction instruction that made the invocations
+    pub index: u8,
+    instructions: Vec<CompiledInstruction>,
+}
+
+impl From<CompiledInnerInstruction> for WasmInnerInstructions {
+    fn from(inner: CompiledInnerInstruction) -> Self {
+        Self {
+            index: inner.index,
+            instructions: inner.instructions,
+        }
+    }
+}
+
+#[wasm_bindgen(js_class = InnerInstructions)]
+impl WasmInnerInstructions {
+    pub fn instructions(&self) -> Vec<JsValue> {
+        self.instructions
+            .iter()
+            .map(|ix| WasmCompiledInstruction::from(ix.to_owned()).into())
+            .collect()
+    }
+}
+
+#[wasm_bindgen(js_name = CompiledInstruction)]
+pub struct WasmCompiledInstruction {
+    #[wasm_bindgen(js_name = programIdIndex)]
+    pub program_id_index: u8,
+    #[wasm_bindgen(getter_with_clone)]
+    pub accounts: Vec<u8>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub data: Vec<u8>,
+}
+
+impl From<CompiledInstruction> for WasmCompiledInstruction {
+    fn from(ix: CompiledInstruction) -> Self {
+        Self {
+            program_id_index: ix.program_id_index,
+            accounts: ix.accounts,
+            data: ix.data,
+        }
+    }
+}
+
+fn inner_instructions_to_js(inner_instructions: &[CompiledInnerInstruction]) -> Vec<JsValue> {
+    inner_instructions
+        .iter()
+        .map(|inner| WasmInnerInstructions::from(inner.to_owned()).into())
+        .collect()
+}
+
+#[wasm_bindgen]
+pub struct SendTransactionResult {
+    result: std::result::Result<String, TransactionError>,
+}
+
+impl SendTransactionResult {
+    pub fn new(tx_hash: String) -> Self {
+        Self {
+            result: Ok(tx_hash),
+        }
+    }
+
+    pub fn new_error(err: TransactionError) -> Self {
+        Self { result: Err(err) }
+    }
+}
+
+#[wasm_bindgen]
+impl SendTransactionResult {
+    pub fn error(&self) -> Option<String> {
+        match &self.result {
+            Ok(_) => None,
+            Err(err) => Some(err.to_string()),
+        }
+    }
+
+    #[wasm_bindgen(js_name = txHash)]
+    pub fn tx_hash(&self) -> Result<String> {
+        self.result
+            .as_ref()
+            .map(|tx_hash| tx_hash.to_owned())
+            .map_err(|err| PlaynetError::Transaction(err.to_owned()))
+    }
+}
+
+#[wasm_bindgen]
+pub struct GetSignatureStatusesResult {
+    statuses: Vec<Option<TransactionStatus>>,
+}
+
+impl GetSignatureStatusesResult {
+    pub fn new(statuses: Vec<Option<TransactionStatus>>) -> Self {
+        Self { statuses }
+    }
+}
+
+#[wasm_bindgen]
+impl GetSignatureStatusesResult {
+    pub fn statuses(self) -> Vec<JsValue> {
+        self.statuses
+            .into_iter()
+            .map(|status| JsValue::from(status.map(WasmTransactionStatus::from)))
+            .collect()
+    }
+}
+
+#[wasm_bindgen(js_name = TransactionStatus)]
+pub struct WasmTransactionStatus {
+    #[wasm_bindgen(js_name = confirmationStatus)]
+    pub confirmation_status: Option<WasmCommitmentLevel>,
+    pub confirmations: Option<usize>,
+    pub slot: Slot,
+    err: Option<TransactionError>,
+}
+
+impl From<TransactionStatus> for WasmTransactionStatus {
+    fn from(val: TransactionStatus) -> Self {
+        Self {
+            confirmation_status: val.confirmation_status.map(WasmCommitmentLevel::from),
+            confirmations: val.confirmations,
+            slot: val.slot,
+            err: val.err,
+        }
+    }
+}
+
+#[wasm_bindgen(js_class = TransactionStatus)]
+impl WasmTransactionStatus {
+    pub fn error(&self) -> Option<String> {
+        self.err.as_ref().map(|err| err.to_string())
+    }
+}
+
+#[wasm_bindgen(js_name = EpochInfo)]
+pub struct WasmEpochInfo {
+    pub epoch: Epoch,
+    #[wasm_bindgen(js_name = slotIndex)]
+    pub slot_index: u64,
+    #[wasm_bindgen(js_name = slotsInEpoch)]
+    pub slots_in_epoch: u64,
+    #[wasm_bindgen(js_name = absoluteSlot)]
+    pub absolute_slot: Slot,
+    #[wasm_bindgen(js_name = blockHeight)]
+    pub block_height: u64,
+    #[wasm_bindgen(js_name = transactionCount)]
+    pub transaction_count: Option<u64>,
+}
+
+impl From<EpochInfo> for WasmEpochInfo {
+    fn from(val: EpochInfo) -> Self {
+        Self {
+            epoch: val.epoch,
+            slot_index: val.slot_index,
+            slots_in_epoch: val.slots_in_epoch,
+            absolute_slot: val.absolute_slot,
+            block_height: val.block_height,
+            transaction_count: val.transaction_count,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = EpochSchedule)]
+pub struct WasmEpochSchedule {
+    #[wasm_bindgen(js_name = slotsPerEpoch)]
+    pub slots_per_epoch: u64,
+    #[wasm_bindgen(js_name = leaderScheduleSlotOffset)]
+    pub leader_schedule_slot_offset: u64,
+    pub warmup: bool,
+    #[wasm_bindgen(js_name = firstNormalEpoch)]
+    pub first_normal_epoch: Epoch,
+    #[wasm_bindgen(js_name = firstNormalSlot)]
+    pub first_normal_slot: Slot,
+}
+
+impl From<EpochSchedule> for WasmEpochSchedule {
+    fn from(val: EpochSchedule) -> Self {
+        Self {
+            slots_per_epoch: val.slots_per_epoch,
+            leader_schedule_slot_offset: val.leader_schedule_slot_offset,
+            warmup: val.warmup,
+            first_normal_epoch: val.first_normal_epoch,
+            first_normal_slot: val.first_normal_slot,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = Supply)]
+pub struct WasmSupply {
+    pub total: u64,
+    pub circulating: u64,
+    #[wasm_bindgen(js_name = nonCirculating)]
+    pub non_circulating: u64,
+}
+
+impl From<Supply> for WasmSupply {
+    fn from(val: Supply) -> Self {
+        Self {
+            total: val.total,
+            circulating: val.circulating,
+            non_circulating: val.non_circulating,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = InflationRate)]
+pub struct WasmInflationRate {
+    pub total: f64,
+    pub validator: f64,
+    pub foundation: f64,
+    pub epoch: Epoch,
+}
+
+impl From<InflationRate> for WasmInflationRate {
+    fn from(val: InflationRate) -> Self {
+        Self {
+            total: val.total,
+            validator: val.validator,
+            foundation: val.foundation,
+            epoch: val.epoch,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = SignatureInfo)]
+pub struct WasmSignatureInfo {
+    signature: Signature,
+    pub slot: Slot,
+    err: Option<TransactionError>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub memo: Option<String>,
+    #[wasm_bindgen(js_name = blockTime)]
+    pub block_time: Option<UnixTimestamp>,
+    #[wasm_bindgen(js_name = confirmationStatus)]
+    pub confirmation_status: Option<WasmCommitmentLevel>,
+}
+
+impl From<SignatureInfo> for WasmSignatureInfo {
+    fn from(val: SignatureInfo) -> Self {
+        Self {
+            signature: val.signature,
+            slot: val.slot,
+            err: val.err,
+            memo: val.memo,
+            block_time: val.block_time,
+            confirmation_status: val.confirmation_status.map(WasmCommitmentLevel::from),
+        }
+    }
+}
+
+#[wasm_bindgen(js_class = SignatureInfo)]
+impl WasmSignatureInfo {
+    pub fn signature(&self) -> String {
+        self.signature.to_string()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.err.as_ref().map(|err| err.to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub struct GetTransactionResult {
+    data: Option<TransactionData>,
+}
+
+impl GetTransactionResult {
+    pub fn new(data: Option<TransactionData>) -> Self {
+        Self { data }
+    }
+
+    fn data(&self) -> Result<&TransactionData> {
+        self.data.as_ref().ok_or(PlaynetError::TransactionNotFound)
+    }
+}
+
+#[wasm_bindgen]
+impl GetTransactionResult {
+    /// NOTE: This method should be called before accessing any other data
+    pub fn exists(&self) -> bool {
+        self.data.is_some()
+    }
+
+    #[wasm_bindgen(js_name = blockTime)]
+    pub fn block_time(&self) -> Result<Option<UnixTimestamp>> {
+        Ok(self.data()?.get_block_time())
+    }
+
+    /// Returns the transaction version or `None` for legacy transactions
+    pub fn version(&self) -> Result<Option<u8>> {
+        Ok(match self.data()?.get_tx().version() {
+            TransactionVersion::Legacy(_) => None,
+            TransactionVersion::Number(version) => Some(version),
+        })
+    }
+
+    pub fn meta(&self) -> Result<Option<WasmConfirmedTransactionMeta>> {
+        Ok(self
+            .data()?
+            .get_meta()
+            .clone()
+            .map(WasmConfirmedTransactionMeta::from))
+    }
+
+    /// Returns the base64 encoded tx string
+    pub fn transaction(&self) -> Result<String> {
+        Ok(base64::encode(bincode::serialize(self.data()?.get_tx())?))
+    }
+}
+
+#[wasm_bindgen]
+pub struct GetBlockResult {
+    block: Option<ConfirmedBlock>,
+}
+
+impl GetBlockResult {
+    pub fn new(block: Option<ConfirmedBlock>) -> Self {
+        Self { block }
+    }
+
+    fn block(&self) -> Result<&ConfirmedBlock> {
+        self.block.as_ref().ok_or(PlaynetError::BlockNotFound)
+    }
+}
+
+#[wasm_bindgen]
+impl GetBlockResult {
+    /// NOTE: This method should be called before accessing any other data
+    pub fn exists(&self) -> bool {
+        self.block.is_some()
+    }
+
+    pub fn blockhash(&self) -> Result<String> {
+        Ok(self.block()?.block.blockhash.to_string())
+    }
+
+    #[wasm_bindgen(js_name = previousBlockhash)]
+    pub fn previous_blockhash(&self) -> Result<String> {
+        Ok(self.block()?.block.previous_blockhash.to_string())
+    }
+
+    #[wasm_bindgen(js_name = parentSlot)]
+    pub fn parent_slot(&self) -> Result<Slot> {
+        Ok(self.block()?.block.parent_slot)
+    }
+
+    #[wasm_bindgen(js_name = blockTime)]
+    pub fn block_time(&self) -> Result<Option<UnixTimestamp>> {
+        Ok(self.block()?.block.block_time)
+    }
+
+    #[wasm_bindgen(js_name = blockHeight)]
+    pub fn block_height(&self) -> Result<u64> {
+        Ok(self.block()?.block.block_height)
+    }
+
+    pub fn signatures(&self) -> Result<Vec<JsValue>> {
+        Ok(self
+            .block()?
+            .block
+            .signatures
+            .iter()
+            .map(|signature| JsValue::from(signature.to_string()))
+            .collect())
+    }
+
+    /// Returns the transactions as `GetTransactionResult`s
+    pub fn transactions(&self) -> Result<Vec<JsValue>> {
+        Ok(self
+            .block()?
+            .transactions
+            .iter()
+            .map(|tx_data| JsValue::from(GetTransactionResult::new(Some(tx_data.clone()))))
+            .collect())
+    }
+}
+
+/// Metadata for a confirmed transaction on the ledger
+#[wasm_bindgen(js_name = ConfirmedTransactionMeta)]
+pub struct WasmConfirmedTransactionMeta {
+    inner: ConfirmedTransactionMeta,
+}
+
+impl From<ConfirmedTransactionMeta> for WasmConfirmedTransactionMeta {
+    fn from(inner: ConfirmedTransactionMeta) -> Self {
+        Self { inner }
+    }
+}
+
+#[wasm_bindgen(js_class = ConfirmedTransactionMeta)]
+impl WasmConfirmedTransactionMeta {
+    pub fn fee(&self) -> u64 {
+        self.inner.fee
+    }
+
+    #[wasm_bindgen(js_name = innerInstructions)]
+    pub fn inner_instructions(&self) -> Option<Vec<JsValue>> {
+        self.inner
+            .inner_instructions
+            .as_deref()
+            .map(inner_instructions_to_js)
+    }
+
+    #[wasm_bindgen(js_name = preBalances)]
+    pub fn pre_balances(&self) -> Vec<u64> {
+        self.inner.pre_balances.to_owned()
+    }
+
+    #[wasm_bindgen(js_name = postBalances)]
+    pub fn post_balances(&self) -> Vec<u64> {
+        self.inner.post_balances.to_owned()
+    }
+
+    #[wasm_bindgen(js_name = logs)]
+    pub fn log_messages(&self) -> Option<Vec<JsValue>> {
+        self.inner
+            .log_messages
+            .as_ref()
+            .map(|logs| logs.iter().map(|log| JsValue::from_str(log)).collect())
+    }
+
+    /// TODO:
+    #[wasm_bindgen(js_name = preTokenBalances)]
+    pub fn pre_token_balances(&self) -> Option<u8> {
+        None
+    }
+
+    /// TODO:
+    #[wasm_bindgen(js_name = postTokenBalances)]
+    pub fn post_token_balances(&self) -> Option<u8> {
+        None
+    }
+
+    pub fn err(&self) -> Option<String> {
+        self.inner.err.as_ref().map(|err| err.to_string())
+    }
+
+    /// TODO:
+    #[wasm_bindgen(js_name = loadedAddresses)]
+    pub fn loaded_addresses(&self) -> Option<u8> {
+        None
+    }
+
+    #[wasm_bindgen(js_name = computeUnitsConsumed)]
+    pub fn compute_units_consumed(&self) -> Option<u64> {
+        self.inner.compute_units_consumed
+    }
+
+    #[wasm_bindgen(js_name = instructionComputeUnits)]
+    pub fn instruction_compute_units(&self) -> Option<Vec<JsValue>> {
+        self.inner
+            .instruction_compute_units
+            .as_deref()
+            .map(instruction_compute_units_to_js)
+    }
+
+    #[wasm_bindgen(js_name = programDetails)]
+    pub fn program_details(&self) -> Option<Vec<JsValue>> {
+        self.inner
+            .program_details
+            .as_deref()
+            .map(program_details_to_js)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum WasmCommitmentLevel {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl From<CommitmentLevel> for WasmCommitmentLevel {
+    fn from(val: CommitmentLevel) -> Self {
+        match val {
+            CommitmentLevel::Processed => Self::Processed,
+            CommitmentLevel::Confirmed => Self::Confirmed,
+            CommitmentLevel::Finalized => Self::Finalized,
+        }
+    }
+}
+
+impl From<WasmCommitmentLevel> for CommitmentLevel {
+    fn from(val: WasmCommitmentLevel) -> Self {
+        match val {
+            WasmCommitmentLevel::Processed => Self::Processed,
+            WasmCommitmentLevel::Confirmed => Self::Confirmed,
+            WasmCommitmentLevel::Finalized => Self::Finalized,
+        }
+    }
+}
+
+/// Reads without a commitment level see the finalized state, like on the
+/// Solana RPC
+pub(super) fn commitment_or_finalized(commitment: Option<WasmCommitmentLevel>) -> CommitmentLevel {
+    commitment
+        .map(CommitmentLevel::from)
+        .unwrap_or(CommitmentLevel::Finalized)
+}
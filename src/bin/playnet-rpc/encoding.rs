@@ -0,0 +1,327 @@
+// Conversions between the bank types and their JSON representations in the
+// Solana RPC.
+
+use {
+    crate::jsonrpc::{RpcError, RpcResult},
+    playnet::{
+        account_decoder::ParsedAccount,
+        runtime::transaction_history::{
+            CompiledInnerInstruction, ConfirmedTransactionMeta, InstructionComputeUnits,
+            ProgramExecutionDetails,
+        },
+        types::TransactionBinaryEncoding,
+        utils::decode_tx,
+    },
+    serde::Deserialize,
+    serde_json::{json, Value},
+    solana_sdk::{
+        account::Account,
+        message::VersionedMessage,
+        transaction::{TransactionError, VersionedTransaction},
+    },
+    std::fmt,
+};
+
+/// Base58 encoding is slow, validators refuse to encode more than this many bytes
+const MAX_BASE58_BYTES: usize = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UiAccountEncoding {
+    /// Legacy base58 encoding, returns the data as a plain string
+    Binary,
+    Base58,
+    Base64,
+    JsonParsed,
+    #[serde(rename = "base64+zstd")]
+    Base64Zstd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UiTransactionEncoding {
+    /// Legacy base58 encoding, returns the transaction as a plain string
+    Binary,
+    Base58,
+    Base64,
+    Json,
+    JsonParsed,
+}
+
+impl fmt::Display for UiTransactionEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let encoding = match self {
+            Self::Binary => "binary",
+            Self::Base58 => "base58",
+            Self::Base64 => "base64",
+            Self::Json => "json",
+            Self::JsonParsed => "jsonParsed",
+        };
+        write!(f, "{}", encoding)
+    }
+}
+
+/// Part of the account data to return
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct DataSlice {
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// Encodes the account the way `getAccountInfo` returns it.
+///
+/// `parsed` is the decoded data for the `jsonParsed` encoding, the data slice
+/// doesn't apply to it.
+pub fn encode_account(
+    account: &Account,
+    encoding: UiAccountEncoding,
+    data_slice: Option<DataSlice>,
+    parsed: Option<ParsedAccount>,
+) -> RpcResult {
+    let data = match data_slice {
+        Some(DataSlice { offset, length }) => {
+            let start = offset.min(account.data.len());
+            let end = offset.saturating_add(length).min(account.data.len());
+            &account.data[start..end]
+        }
+        None => &account.data[..],
+    };
+
+    let data = match encoding {
+        UiAccountEncoding::JsonParsed if parsed.is_some() => json!(parsed),
+        UiAccountEncoding::Binary => json!(encode_base58(data)?),
+        UiAccountEncoding::Base58 => json!([encode_base58(data)?, "base58"]),
+        // Accounts without a parser fall back to base64
+        UiAccountEncoding::Base64 | UiAccountEncoding::JsonParsed => {
+            json!([base64::encode(data), "base64"])
+        }
+        UiAccountEncoding::Base64Zstd => {
+            let compressed = zstd::encode_all(data, 0)
+                .map_err(|err| RpcError::internal_error(err.to_string()))?;
+            json!([base64::encode(compressed), "base64+zstd"])
+        }
+    };
+
+    Ok(json!({
+        "data": data,
+        "executable": account.executable,
+        "lamports": account.lamports,
+        "owner": account.owner.to_string(),
+        "rentEpoch": account.rent_epoch,
+        "space": account.data.len(),
+    }))
+}
+
+fn encode_base58(data: &[u8]) -> RpcResult<String> {
+    if data.len() > MAX_BASE58_BYTES {
+        return Err(RpcError::invalid_request(format!(
+            "Encoded binary (base 58) data should be less than {} bytes, please use Base64 encoding.",
+            MAX_BASE58_BYTES
+        )));
+    }
+
+    Ok(bs58::encode(data).into_string())
+}
+
+/// Decodes a wire format transaction sent to `sendTransaction` or `simulateTransaction`
+pub fn decode_transaction(
+    encoded_tx: &str,
+    encoding: UiTransactionEncoding,
+) -> RpcResult<VersionedTransaction> {
+    let encoding = match encoding {
+        UiTransactionEncoding::Binary | UiTransactionEncoding::Base58 => {
+            TransactionBinaryEncoding::Base58
+        }
+        UiTransactionEncoding::Base64 => TransactionBinaryEncoding::Base64,
+        _ => {
+            return Err(RpcError::invalid_params(format!(
+                "unsupported encoding: {}. Supported encodings: base58, base64",
+                encoding
+            )))
+        }
+    };
+
+    decode_tx(encoded_tx, encoding).map_err(|err| RpcError::invalid_params(err.to_string()))
+}
+
+/// Encodes the transaction the way `getTransaction` returns it
+pub fn encode_transaction(tx: &VersionedTransaction, encoding: UiTransactionEncoding) -> RpcResult {
+    let serialize =
+        || bincode::serialize(tx).map_err(|err| RpcError::internal_error(err.to_string()));
+
+    Ok(match encoding {
+        UiTransactionEncoding::Binary => json!(bs58::encode(serialize()?).into_string()),
+        UiTransactionEncoding::Base58 => {
+            json!([bs58::encode(serialize()?).into_string(), "base58"])
+        }
+        UiTransactionEncoding::Base64 => json!([base64::encode(serialize()?), "base64"]),
+        UiTransactionEncoding::Json => json!({
+            "signatures": tx.signatures.iter().map(|signature| signature.to_string()).collect::<Vec<_>>(),
+            "message": encode_message(&tx.message),
+        }),
+        UiTransactionEncoding::JsonParsed => {
+            return Err(RpcError::invalid_params(format!(
+                "unsupported encoding: {}. Supported encodings: base58, base64, json",
+                encoding
+            )))
+        }
+    })
+}
+
+fn encode_message(message: &VersionedMessage) -> Value {
+    let header = message.header();
+    let mut value = json!({
+        "accountKeys": message
+            .static_account_keys()
+            .iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>(),
+        "header": {
+            "numRequiredSignatures": header.num_required_signatures,
+            "numReadonlySignedAccounts": header.num_readonly_signed_accounts,
+            "numReadonlyUnsignedAccounts": header.num_readonly_unsigned_accounts,
+        },
+        "recentBlockhash": message.recent_blockhash().to_string(),
+        "instructions": message
+            .instructions()
+            .iter()
+            .map(|ix| json!({
+                "programIdIndex": ix.program_id_index,
+                "accounts": ix.accounts,
+                "data": bs58::encode(&ix.data).into_string(),
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    if let VersionedMessage::V0(message) = message {
+        value["addressTableLookups"] = message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| {
+                json!({
+                    "accountKey": lookup.account_key.to_string(),
+                    "writableIndexes": lookup.writable_indexes,
+                    "readonlyIndexes": lookup.readonly_indexes,
+                })
+            })
+            .collect();
+    }
+
+    value
+}
+
+/// Encodes the cross program invocations of the instructions
+pub fn encode_inner_instructions(inner_instructions: &[CompiledInnerInstruction]) -> Value {
+    inner_instructions
+        .iter()
+        .map(|inner| {
+            json!({
+                "index": inner.index,
+                "instructions": inner
+                    .instructions
+                    .iter()
+                    .map(|ix| json!({
+                        "programIdIndex": ix.program_id_index,
+                        "accounts": ix.accounts,
+                        "data": bs58::encode(&ix.data).into_string(),
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+/// Encodes the compute units of the instructions and their cross program
+/// invocations as a tree
+pub fn encode_instruction_compute_units(compute_units: &[InstructionComputeUnits]) -> Value {
+    compute_units
+        .iter()
+        .map(|compute_units| {
+            json!({
+                "programId": compute_units.program_id.to_string(),
+                "unitsConsumed": compute_units.units_consumed,
+                "innerInstructions": encode_instruction_compute_units(
+                    &compute_units.inner_instructions
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Encodes the execution details of the programs, the timings are in
+/// microseconds
+pub fn encode_program_details(program_details: &[ProgramExecutionDetails]) -> Value {
+    program_details
+        .iter()
+        .map(|details| {
+            json!({
+                "programId": details.program_id.to_string(),
+                "count": details.count,
+                "unitsConsumed": details.units_consumed,
+                "erroredUnits": details.errored_units,
+                "timings": {
+                    "getOrCreateExecutorUs": details.timings.get_or_create_executor_us,
+                    "serializeUs": details.timings.serialize_us,
+                    "createVmUs": details.timings.create_vm_us,
+                    "executeUs": details.timings.execute_us,
+                    "deserializeUs": details.timings.deserialize_us,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Encodes the transaction metadata the way `getTransaction` returns it
+pub fn encode_transaction_meta(meta: &ConfirmedTransactionMeta) -> Value {
+    let inner_instructions = meta
+        .inner_instructions
+        .as_deref()
+        .map(encode_inner_instructions)
+        .unwrap_or_else(|| json!([]));
+    let (writable, readonly) = meta
+        .loaded_addresses
+        .as_ref()
+        .map(|addresses| {
+            (
+                addresses
+                    .writable
+                    .iter()
+                    .map(|key| key.to_string())
+                    .collect(),
+                addresses
+                    .readonly
+                    .iter()
+                    .map(|key| key.to_string())
+                    .collect(),
+            )
+        })
+        .unwrap_or((vec![], vec![]));
+
+    json!({
+        "err": meta.err,
+        "status": encode_status(&meta.err),
+        "fee": meta.fee,
+        "preBalances": meta.pre_balances,
+        "postBalances": meta.post_balances,
+        "innerInstructions": inner_instructions,
+        "logMessages": meta.log_messages,
+        "preTokenBalances": [],
+        "postTokenBalances": [],
+        "rewards": [],
+        "loadedAddresses": { "writable": writable, "readonly": readonly },
+        "computeUnitsConsumed": meta.compute_units_consumed,
+        "instructionComputeUnits": meta
+            .instruction_compute_units
+            .as_deref()
+            .map(encode_instruction_compute_units),
+        "programDetails": meta.program_details.as_deref().map(encode_program_details),
+    })
+}
+
+/// Deprecated `status` field that is still returned next to `err`
+pub fn encode_status(err: &Option<TransactionError>) -> Value {
+    match err {
+        Some(err) => json!({ "Err": err }),
+        None => json!({ "Ok": null }),
+    }
+}
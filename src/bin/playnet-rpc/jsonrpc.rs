@@ -0,0 +1,271 @@
+// JSON-RPC 2.0 envelope handling.
+//
+// Error codes and messages follow the ones returned by the Solana RPC so that
+// clients can handle Playnet errors the same way they handle cluster errors.
+
+use {
+    serde::de::DeserializeOwned,
+    serde_json::{json, Value},
+};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+pub const BLOCK_CLEANED_UP: i64 = -32001;
+pub const SEND_TRANSACTION_PREFLIGHT_FAILURE: i64 = -32002;
+pub const TRANSACTION_SIGNATURE_VERIFICATION_FAILURE: i64 = -32003;
+pub const BLOCK_NOT_AVAILABLE: i64 = -32004;
+pub const TRANSACTION_PRECOMPILE_VERIFICATION_FAILURE: i64 = -32006;
+pub const UNSUPPORTED_TRANSACTION_VERSION: i64 = -32015;
+pub const MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+
+#[derive(Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+pub type RpcResult<T = Value> = Result<T, RpcError>;
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn parse_error() -> Self {
+        Self::new(PARSE_ERROR, "Parse error")
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(INVALID_REQUEST, message)
+    }
+
+    pub fn method_not_found() -> Self {
+        Self::new(METHOD_NOT_FOUND, "Method not found")
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    pub fn internal_error(message: impl Into<String>) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+
+    fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+
+        error
+    }
+}
+
+/// Positional params of a request
+pub struct Params(Vec<Value>);
+
+impl Params {
+    fn parse(params: Option<Value>) -> RpcResult<Self> {
+        match params {
+            None | Some(Value::Null) => Ok(Self(vec![])),
+            Some(Value::Array(params)) => Ok(Self(params)),
+            Some(_) => Err(RpcError::invalid_params(
+                "Invalid params: expected an array of positional params",
+            )),
+        }
+    }
+
+    /// Deserializes the param at `index`, failing if it's missing
+    pub fn required<T: DeserializeOwned>(&self, index: usize) -> RpcResult<T> {
+        let param = self.0.get(index).ok_or_else(|| {
+            RpcError::invalid_params(format!(
+                "`params` should have at least {} argument(s)",
+                index + 1
+            ))
+        })?;
+
+        Self::deserialize(param.clone())
+    }
+
+    /// Deserializes the param at `index`, `None` if it's missing or `null`
+    pub fn optional<T: DeserializeOwned>(&self, index: usize) -> RpcResult<Option<T>> {
+        match self.0.get(index) {
+            None | Some(Value::Null) => Ok(None),
+            Some(param) => Self::deserialize(param.clone()).map(Some),
+        }
+    }
+
+    /// Same as `optional` but returns the default value for missing params
+    pub fn optional_or_default<T: DeserializeOwned + Default>(&self, index: usize) -> RpcResult<T> {
+        self.optional(index).map(Option::unwrap_or_default)
+    }
+
+    fn deserialize<T: DeserializeOwned>(param: Value) -> RpcResult<T> {
+        serde_json::from_value(param)
+            .map_err(|err| RpcError::invalid_params(format!("Invalid params: {}", err)))
+    }
+}
+
+/// Handles a single or a batch request body and returns the response body
+pub fn handle_body<F>(body: &[u8], handler: F) -> Value
+where
+    F: Fn(&str, Params) -> RpcResult,
+{
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(requests)) if !requests.is_empty() => Value::Array(
+            requests
+                .into_iter()
+                .map(|request| handle_request(request, &handler))
+                .collect(),
+        ),
+        Ok(Value::Array(_)) => {
+            error_response(Value::Null, RpcError::invalid_request("Invalid request"))
+        }
+        Ok(request) => handle_request(request, &handler),
+        Err(_) => error_response(Value::Null, RpcError::parse_error()),
+    }
+}
+
+fn handle_request<F>(mut request: Value, handler: &F) -> Value
+where
+    F: Fn(&str, Params) -> RpcResult,
+{
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = match (request.get("jsonrpc"), request.get("method")) {
+        (Some(Value::String(version)), Some(Value::String(method))) if version == "2.0" => {
+            method.to_owned()
+        }
+        _ => return error_response(id, RpcError::invalid_request("Invalid request")),
+    };
+
+    let result = Params::parse(request.get_mut("params").map(Value::take))
+        .and_then(|params| handler(&method, params));
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(err) => error_response(id, err),
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "error": err.to_json(), "id": id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(body: &str) -> Value {
+        handle_body(body.as_bytes(), |method, params| match method {
+            "echo" => params.required::<Value>(0),
+            "fail" => Err(RpcError::internal_error("Failed").with_data(json!({ "logs": [] }))),
+            _ => Err(RpcError::method_not_found()),
+        })
+    }
+
+    fn invalid_request(id: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "error": { "code": INVALID_REQUEST, "message": "Invalid request" },
+            "id": id,
+        })
+    }
+
+    #[test]
+    fn single_request() {
+        assert_eq!(
+            handle(r#"{"jsonrpc":"2.0","id":1,"method":"echo","params":["hi"]}"#),
+            json!({ "jsonrpc": "2.0", "result": "hi", "id": 1 })
+        );
+        assert_eq!(
+            handle(r#"{"jsonrpc":"2.0","id":"a","method":"fail"}"#),
+            json!({
+                "jsonrpc": "2.0",
+                "error": { "code": INTERNAL_ERROR, "message": "Failed", "data": { "logs": [] } },
+                "id": "a",
+            })
+        );
+    }
+
+    #[test]
+    fn batch_request() {
+        let response = handle(
+            r#"[
+                {"jsonrpc":"2.0","id":1,"method":"echo","params":[1]},
+                {"jsonrpc":"2.0","id":2,"method":"unknown"},
+                {"jsonrpc":"1.0","id":3,"method":"echo","params":[3]},
+                {"jsonrpc":"2.0","id":4,"method":"echo"},
+                {"jsonrpc":"2.0","id":5,"method":"echo","params":{"a":1}}
+            ]"#,
+        );
+        assert_eq!(
+            response,
+            json!([
+                { "jsonrpc": "2.0", "result": 1, "id": 1 },
+                {
+                    "jsonrpc": "2.0",
+                    "error": { "code": METHOD_NOT_FOUND, "message": "Method not found" },
+                    "id": 2,
+                },
+                invalid_request(json!(3)),
+                {
+                    "jsonrpc": "2.0",
+                    "error": {
+                        "code": INVALID_PARAMS,
+                        "message": "`params` should have at least 1 argument(s)",
+                    },
+                    "id": 4,
+                },
+                {
+                    "jsonrpc": "2.0",
+                    "error": {
+                        "code": INVALID_PARAMS,
+                        "message": "Invalid params: expected an array of positional params",
+                    },
+                    "id": 5,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_batch() {
+        assert_eq!(handle("[]"), invalid_request(Value::Null));
+    }
+
+    #[test]
+    fn invalid_version() {
+        assert_eq!(
+            handle(r#"{"jsonrpc":"1.0","id":1,"method":"echo","params":[1]}"#),
+            invalid_request(json!(1))
+        );
+        assert_eq!(
+            handle(r#"{"id":1,"method":"echo","params":[1]}"#),
+            invalid_request(json!(1))
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(
+            handle(r#"{"jsonrpc":"2.0","id":1,"#),
+            json!({
+                "jsonrpc": "2.0",
+                "error": { "code": PARSE_ERROR, "message": "Parse error" },
+                "id": null,
+            })
+        );
+    }
+}
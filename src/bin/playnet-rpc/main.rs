@@ -0,0 +1,290 @@
+// Serves a Playnet bank over a Solana compatible JSON-RPC HTTP endpoint and a
+// PubSub WebSocket endpoint, so that unmodified clients like `@solana/web3.js`
+// and the `solana` CLI can use it:
+//
+// playnet-rpc [--bind 127.0.0.1:8899] [--ws-bind 127.0.0.1:8900]
+//             [--ledger <dir|genesis.bin> [--faucet-keypair <file>]] [--snapshot <file>]
+//             [--accounts <dir>] [--threads <n>]
+//             [--faucet-per-request-sol-cap <SOL>] [--faucet-per-account-sol-cap <SOL>]
+//             [--block-production <per-transaction|batch:<n>|interval:<ms>>]
+//             [--log-bytes-limit <bytes|unlimited>] [--bpf-trace-dir <dir>]
+//
+// Banks created from a ledger don't create lamports for the faucet, the
+// airdrops come from the genesis account of `--faucet-keypair`.
+//
+// `--bpf-trace-dir` traces the instructions that BPF programs execute and
+// writes the disassembly of every invocation to
+// `<dir>/<signature>/<position>-<program id>.trace`, simulations included.
+
+mod encoding;
+mod jsonrpc;
+mod methods;
+mod pubsub;
+
+use {
+    crate::{jsonrpc::handle_body, methods::RpcHandler, pubsub::start_pubsub_server},
+    playnet::{
+        rpc::PgRpc,
+        runtime::{
+            bank::PgBank, block_production::BlockProduction, bpf_tracer::BpfTracing,
+            faucet::AirdropLimits, snapshot::read_snapshot,
+        },
+        types::LogLimit,
+    },
+    solana_sdk::{native_token::sol_to_lamports, signature::read_keypair_file},
+    std::{
+        env,
+        fs::File,
+        io::{BufReader, Read},
+        net::SocketAddr,
+        path::PathBuf,
+        process,
+        sync::{Arc, RwLock},
+        thread,
+    },
+    tiny_http::{Header, Method, Request, Response, Server},
+    tracing::{debug, error, info},
+    tracing_subscriber::EnvFilter,
+};
+
+/// Same limit as the Solana RPC
+const MAX_REQUEST_BODY_SIZE: u64 = 50 * (1 << 20);
+
+struct Args {
+    bind: SocketAddr,
+    /// Defaults to the port after the HTTP port like `solana-test-validator`
+    ws_bind: Option<SocketAddr>,
+    ledger: Option<PathBuf>,
+    faucet_keypair: Option<PathBuf>,
+    snapshot: Option<PathBuf>,
+    accounts: Option<PathBuf>,
+    threads: usize,
+    airdrop_limits: AirdropLimits,
+    block_production: BlockProduction,
+    log_limit: LogLimit,
+    bpf_tracing: BpfTracing,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Self {
+            bind: ([127, 0, 0, 1], 8899).into(),
+            ws_bind: None,
+            ledger: None,
+            faucet_keypair: None,
+            snapshot: None,
+            accounts: None,
+            threads: 4,
+            airdrop_limits: AirdropLimits::default(),
+            block_production: BlockProduction::default(),
+            log_limit: LogLimit::default(),
+            bpf_tracing: BpfTracing::default(),
+        };
+
+        let mut iter = env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or(format!("Missing value for `{}`", arg));
+            match arg.as_str() {
+                "--bind" => {
+                    args.bind = value()?
+                        .parse()
+                        .map_err(|err| format!("Invalid bind address: {}", err))?
+                }
+                "--ws-bind" => {
+                    args.ws_bind = Some(
+                        value()?
+                            .parse()
+                            .map_err(|err| format!("Invalid WebSocket bind address: {}", err))?,
+                    )
+                }
+                "--ledger" => args.ledger = Some(value()?.into()),
+                "--faucet-keypair" => args.faucet_keypair = Some(value()?.into()),
+                "--snapshot" => args.snapshot = Some(value()?.into()),
+                "--accounts" => args.accounts = Some(value()?.into()),
+                "--threads" => {
+                    args.threads = value()?
+                        .parse()
+                        .map_err(|err| format!("Invalid thread count: {}", err))?
+                }
+                "--faucet-per-request-sol-cap" => {
+                    args.airdrop_limits.per_request_cap = Some(parse_sol(&value()?)?)
+                }
+                "--faucet-per-account-sol-cap" => {
+                    args.airdrop_limits.per_account_cap = Some(parse_sol(&value()?)?)
+                }
+                "--block-production" => {
+                    args.block_production = match value()?.parse() {
+                        // Nothing would end the slots since there is no RPC method for it
+                        Ok(BlockProduction::Manual) => {
+                            return Err("Manual block production is not supported".into())
+                        }
+                        Ok(block_production) => block_production,
+                        Err(err) => return Err(err.to_string()),
+                    }
+                }
+                "--log-bytes-limit" => {
+                    args.log_limit = match value()?.as_str() {
+                        "unlimited" => LogLimit::Unlimited,
+                        bytes => LogLimit::Bytes(
+                            bytes
+                                .parse()
+                                .map_err(|err| format!("Invalid log bytes limit: {}", err))?,
+                        ),
+                    }
+                }
+                "--bpf-trace-dir" => args.bpf_tracing = BpfTracing::Directory(value()?.into()),
+                _ => return Err(format!("Unknown argument `{}`", arg)),
+            }
+        }
+
+        Ok(args)
+    }
+
+    fn create_bank(&self) -> Result<PgBank, String> {
+        if self.faucet_keypair.is_some() && self.ledger.is_none() {
+            return Err("`--faucet-keypair` requires `--ledger`".into());
+        }
+        let faucet_keypair = match &self.faucet_keypair {
+            Some(path) => Some(
+                read_keypair_file(path)
+                    .map_err(|err| format!("Failed to read the faucet keypair: {}", err))?,
+            ),
+            None => None,
+        };
+
+        let mut bank = match (&self.ledger, &self.snapshot) {
+            (Some(_), Some(_)) => return Err("`--ledger` and `--snapshot` are exclusive".into()),
+            (Some(ledger), None) => PgBank::from_genesis_file(ledger, faucet_keypair),
+            (None, Some(snapshot)) => File::open(snapshot)
+                .map_err(Into::into)
+                .and_then(|file| read_snapshot(BufReader::new(file))),
+            (None, None) => PgBank::new(None),
+        }
+        .map_err(|err| format!("Failed to create the bank: {}", err))?;
+
+        if let Some(accounts) = &self.accounts {
+            let count = bank
+                .load_accounts(accounts)
+                .map_err(|err| format!("Failed to load the accounts: {}", err))?;
+            info!(count, "Loaded accounts");
+        }
+
+        bank.set_airdrop_limits(self.airdrop_limits);
+        info!(faucet = %bank.get_faucet_pubkey(), "Faucet ready");
+
+        bank.set_block_production(self.block_production);
+        bank.set_log_limit(self.log_limit);
+        bank.set_bpf_tracing(self.bpf_tracing.clone());
+
+        Ok(bank)
+    }
+}
+
+fn parse_sol(value: &str) -> Result<u64, String> {
+    value
+        .parse::<f64>()
+        .map(sol_to_lamports)
+        .map_err(|err| format!("Invalid SOL amount: {}", err))
+}
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let args = Args::parse().and_then(|args| args.create_bank().map(|bank| (args, bank)));
+    let (args, bank) = match args {
+        Ok(args) => args,
+        Err(err) => {
+            error!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    let server = match Server::http(args.bind) {
+        Ok(server) => Arc::new(server),
+        Err(err) => {
+            error!("Failed to bind to {}: {}", args.bind, err);
+            process::exit(1);
+        }
+    };
+    info!(bind = %args.bind, "JSON-RPC server started");
+
+    let rpc = PgRpc::new(Arc::new(RwLock::new(bank)));
+    let ws_bind = args.ws_bind.unwrap_or_else(|| {
+        let mut ws_bind = args.bind;
+        ws_bind.set_port(args.bind.port().saturating_add(1));
+        ws_bind
+    });
+    if let Err(err) = start_pubsub_server(ws_bind, rpc.clone()) {
+        error!("Failed to bind to {}: {}", ws_bind, err);
+        process::exit(1);
+    }
+
+    if let BlockProduction::Interval(interval) = args.block_production {
+        info!(?interval, "Producing blocks on an interval");
+        let rpc = rpc.clone();
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            rpc.advance_slots(1);
+        });
+    }
+
+    let handler = Arc::new(RpcHandler::new(rpc));
+    let workers = (0..args.threads.max(1))
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let handler = Arc::clone(&handler);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle_http_request(request, &handler);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for worker in workers {
+        worker.join().ok();
+    }
+}
+
+fn handle_http_request(mut request: Request, handler: &RpcHandler) {
+    let response = match request.method() {
+        // CORS preflight from browsers
+        Method::Options => Response::from_data(vec![]),
+        Method::Post => {
+            let mut body = vec![];
+            match request
+                .as_reader()
+                .take(MAX_REQUEST_BODY_SIZE)
+                .read_to_end(&mut body)
+            {
+                Ok(_) => {
+                    let response = handle_body(&body, |method, params| {
+                        debug!(method, "Handling request");
+                        handler.handle(method, params)
+                    });
+                    Response::from_data(response.to_string())
+                        .with_header(header("Content-Type", "application/json"))
+                }
+                Err(err) => Response::from_string(err.to_string()).with_status_code(400),
+            }
+        }
+        _ => Response::from_string("Used HTTP Method is not allowed. POST or OPTIONS is required")
+            .with_status_code(405),
+    };
+
+    let response = response
+        .with_header(header("Access-Control-Allow-Origin", "*"))
+        .with_header(header("Access-Control-Allow-Headers", "Content-Type"))
+        .with_header(header("Access-Control-Allow-Methods", "POST, OPTIONS"));
+    if let Err(err) = request.respond(response) {
+        debug!(%err, "Failed to respond");
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("Valid header")
+}
@@ -0,0 +1,962 @@
+// Solana JSON-RPC methods implemented on top of `PgRpc`.
+//
+// Methods accept the same params and return the same shapes as the Solana RPC.
+
+use {
+    crate::{
+        encoding::{
+            decode_transaction, encode_account, encode_inner_instructions,
+            encode_instruction_compute_units, encode_program_details, encode_status,
+            encode_transaction, encode_transaction_meta, DataSlice, UiAccountEncoding,
+            UiTransactionEncoding,
+        },
+        jsonrpc::{
+            Params, RpcError, RpcResult, BLOCK_CLEANED_UP, BLOCK_NOT_AVAILABLE,
+            MIN_CONTEXT_SLOT_NOT_REACHED, SEND_TRANSACTION_PREFLIGHT_FAILURE,
+            TRANSACTION_PRECOMPILE_VERIFICATION_FAILURE,
+            TRANSACTION_SIGNATURE_VERIFICATION_FAILURE, UNSUPPORTED_TRANSACTION_VERSION,
+        },
+    },
+    playnet::{
+        account_decoder::ParsedAccount,
+        error::PlaynetError,
+        rpc::PgRpc,
+        runtime::transaction_history::TransactionData,
+        types::{AccountFilter, CommitmentLevel, SimulateTransactionResult},
+        utils::{parse_pubkey, parse_signature},
+    },
+    serde::Deserialize,
+    serde_json::{json, Value},
+    solana_sdk::{
+        account::Account,
+        feature_set,
+        message::VersionedMessage,
+        pubkey::Pubkey,
+        signature::Signature,
+        slot_history::Slot,
+        transaction::{SanitizedTransaction, TransactionError, TransactionVersion},
+    },
+};
+
+/// Version of the Solana RPC API the responses are compatible with
+pub const API_VERSION: &str = "1.14.6";
+
+/// Maximum number of signatures accepted by `getSignatureStatuses`
+const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
+
+/// Maximum number of transactions returned by `getSignaturesForAddress`
+const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1000;
+
+/// Maximum slot range and limit accepted by `getBlocks` and `getBlocksWithLimit`
+const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
+
+/// Maximum number of accounts accepted by `getMultipleAccounts`
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Maximum number of account filters accepted per request
+const MAX_ACCOUNT_FILTERS: usize = 4;
+
+/// Maximum length of the `memcmp` filter bytes
+const MAX_MEMCMP_BYTES: usize = 128;
+
+/// Commitment level of the requests, the deprecated levels are aliases
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RpcCommitmentLevel {
+    #[serde(alias = "recent")]
+    Processed,
+    #[serde(alias = "single", alias = "singleGossip")]
+    Confirmed,
+    #[serde(alias = "max", alias = "root")]
+    Finalized,
+}
+
+impl From<RpcCommitmentLevel> for CommitmentLevel {
+    fn from(level: RpcCommitmentLevel) -> Self {
+        match level {
+            RpcCommitmentLevel::Processed => Self::Processed,
+            RpcCommitmentLevel::Confirmed => Self::Confirmed,
+            RpcCommitmentLevel::Finalized => Self::Finalized,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitmentConfig {
+    commitment: Option<RpcCommitmentLevel>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountInfoConfig {
+    encoding: Option<UiAccountEncoding>,
+    data_slice: Option<DataSlice>,
+    commitment: Option<RpcCommitmentLevel>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgramAccountsConfig {
+    encoding: Option<UiAccountEncoding>,
+    data_slice: Option<DataSlice>,
+    filters: Option<Vec<RpcFilterType>>,
+    #[serde(default)]
+    with_context: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignaturesForAddressConfig {
+    before: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    commitment: Option<RpcCommitmentLevel>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignatureStatusConfig {
+    #[serde(default)]
+    search_transaction_history: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendTransactionConfig {
+    #[serde(default)]
+    skip_preflight: bool,
+    encoding: Option<UiTransactionEncoding>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateTransactionConfig {
+    #[serde(default)]
+    sig_verify: bool,
+    #[serde(default)]
+    replace_recent_blockhash: bool,
+    encoding: Option<UiTransactionEncoding>,
+    accounts: Option<SimulateTransactionAccountsConfig>,
+    min_context_slot: Option<Slot>,
+    #[serde(default)]
+    inner_instructions: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateTransactionAccountsConfig {
+    encoding: Option<UiAccountEncoding>,
+    addresses: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionConfig {
+    encoding: Option<UiTransactionEncoding>,
+    max_supported_transaction_version: Option<u8>,
+    commitment: Option<RpcCommitmentLevel>,
+}
+
+/// `getTransaction` also accepts the encoding by itself as the config
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TransactionConfigWrapper {
+    Encoding(UiTransactionEncoding),
+    Config(TransactionConfig),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockConfig {
+    encoding: Option<UiTransactionEncoding>,
+    transaction_details: Option<TransactionDetails>,
+    rewards: Option<bool>,
+    max_supported_transaction_version: Option<u8>,
+}
+
+/// `getBlock` also accepts the encoding by itself as the config
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BlockConfigWrapper {
+    Encoding(UiTransactionEncoding),
+    Config(BlockConfig),
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum TransactionDetails {
+    #[default]
+    Full,
+    Signatures,
+    None,
+}
+
+/// Account filter in the `getProgramAccounts` and `programSubscribe` config
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcFilterType {
+    DataSize(u64),
+    Memcmp(RpcMemcmp),
+}
+
+#[derive(Deserialize)]
+pub struct RpcMemcmp {
+    offset: usize,
+    bytes: String,
+    #[serde(default)]
+    encoding: RpcMemcmpEncoding,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RpcMemcmpEncoding {
+    #[default]
+    Base58,
+    Base64,
+}
+
+/// Dispatches the JSON-RPC requests to `PgRpc`
+pub struct RpcHandler {
+    rpc: PgRpc,
+}
+
+impl RpcHandler {
+    pub fn new(rpc: PgRpc) -> Self {
+        Self { rpc }
+    }
+
+    pub fn handle(&self, method: &str, params: Params) -> RpcResult {
+        match method {
+            "getAccountInfo" => self.get_account_info(params),
+            "getBalance" => {
+                let pubkey = parse_pubkey_param(&params.required::<String>(0)?)?;
+                let commitment = commitment_param(&params, 1)?;
+                Ok(self.with_commitment_context(
+                    commitment,
+                    json!(self.rpc.get_balance_with_commitment(&pubkey, commitment)),
+                ))
+            }
+            "getBlock" => self.get_block(params),
+            "getBlocks" => {
+                let start_slot = params.required::<Slot>(0)?;
+                let end_slot = params.optional::<Slot>(1)?;
+                if end_slot
+                    .unwrap_or_else(|| self.rpc.get_slot())
+                    .saturating_sub(start_slot)
+                    > MAX_GET_CONFIRMED_BLOCKS_RANGE
+                {
+                    return Err(RpcError::invalid_params(format!(
+                        "Slot range too large; max {}",
+                        MAX_GET_CONFIRMED_BLOCKS_RANGE
+                    )));
+                }
+
+                Ok(json!(self.rpc.get_blocks(start_slot, end_slot)))
+            }
+            "getBlocksWithLimit" => {
+                let start_slot = params.required::<Slot>(0)?;
+                let limit = params.required::<usize>(1)?;
+                if limit as u64 > MAX_GET_CONFIRMED_BLOCKS_RANGE {
+                    return Err(RpcError::invalid_params(format!(
+                        "Limit too large; max {}",
+                        MAX_GET_CONFIRMED_BLOCKS_RANGE
+                    )));
+                }
+
+                Ok(json!(self.rpc.get_blocks_with_limit(start_slot, limit)))
+            }
+            "getBlockTime" => {
+                let slot = params.required::<Slot>(0)?;
+                match self.rpc.get_block_time(slot) {
+                    Some(block_time) => Ok(json!(block_time)),
+                    None => Err(self.block_error(slot)),
+                }
+            }
+            "getBlockHeight" => {
+                let commitment = commitment_param(&params, 0)?;
+                Ok(json!(self.rpc.get_block_height_with_commitment(commitment)))
+            }
+            "getEpochInfo" => Ok(json!(self.rpc.get_epoch_info())),
+            "getEpochSchedule" => Ok(json!(self.rpc.get_epoch_schedule())),
+            "getFeeForMessage" => self.get_fee_for_message(params),
+            "getFirstAvailableBlock" => Ok(json!(self.rpc.get_first_available_block())),
+            "getGenesisHash" => Ok(json!(self.rpc.get_genesis_hash().to_string())),
+            "getHealth" => Ok(json!("ok")),
+            "getIdentity" => Ok(json!({ "identity": self.rpc.get_identity().to_string() })),
+            "getInflationRate" => {
+                let inflation_rate = self.rpc.get_inflation_rate();
+                Ok(json!({
+                    "total": inflation_rate.total,
+                    "validator": inflation_rate.validator,
+                    "foundation": inflation_rate.foundation,
+                    "epoch": inflation_rate.epoch,
+                }))
+            }
+            "getLatestBlockhash" => {
+                let latest_blockhash = self.rpc.get_latest_blockhash();
+                Ok(self.with_context(json!({
+                    "blockhash": latest_blockhash.blockhash.to_string(),
+                    "lastValidBlockHeight": latest_blockhash.last_valid_block_height,
+                })))
+            }
+            "getMinimumBalanceForRentExemption" => {
+                let data_len = params.required::<usize>(0)?;
+                Ok(json!(self
+                    .rpc
+                    .get_minimum_balance_for_rent_exemption(data_len)))
+            }
+            "getMultipleAccounts" => self.get_multiple_accounts(params),
+            "getProgramAccounts" => self.get_program_accounts(params),
+            "getSignaturesForAddress" => self.get_signatures_for_address(params),
+            "getSignatureStatuses" => self.get_signature_statuses(params),
+            "getSlot" => {
+                let commitment = commitment_param(&params, 0)?;
+                Ok(json!(self.rpc.get_slot_with_commitment(commitment)))
+            }
+            "getSupply" => {
+                let supply = self.rpc.get_supply();
+                Ok(self.with_context(json!({
+                    "total": supply.total,
+                    "circulating": supply.circulating,
+                    "nonCirculating": supply.non_circulating,
+                    "nonCirculatingAccounts": supply
+                        .non_circulating_accounts
+                        .iter()
+                        .map(|pubkey| pubkey.to_string())
+                        .collect::<Vec<_>>(),
+                })))
+            }
+            "getTransaction" => self.get_transaction(params),
+            "getVersion" => Ok(json!({
+                "solana-core": API_VERSION,
+                "feature-set": u32::from_le_bytes(
+                    feature_set::ID.as_ref()[..4].try_into().expect("Hash has 32 bytes"),
+                ),
+            })),
+            "requestAirdrop" => self.request_airdrop(params),
+            "sendTransaction" => self.send_transaction(params),
+            "simulateTransaction" => self.simulate_transaction(params),
+            _ => Err(RpcError::method_not_found()),
+        }
+    }
+
+    /// Wraps the value in the `{ context, value }` response
+    fn with_context(&self, value: Value) -> Value {
+        json!({
+            "context": { "apiVersion": API_VERSION, "slot": self.rpc.get_slot() },
+            "value": value,
+        })
+    }
+
+    /// Wraps the value in the `{ context, value }` response of the highest slot
+    /// that reached the commitment level
+    fn with_commitment_context(&self, commitment: CommitmentLevel, value: Value) -> Value {
+        json!({
+            "context": {
+                "apiVersion": API_VERSION,
+                "slot": self.rpc.get_slot_with_commitment(commitment),
+            },
+            "value": value,
+        })
+    }
+
+    /// Error for the blocks that don't exist
+    fn block_error(&self, slot: Slot) -> RpcError {
+        let first_available_block = self.rpc.get_first_available_block();
+        if slot < first_available_block {
+            RpcError::new(
+                BLOCK_CLEANED_UP,
+                format!(
+                    "Block {} cleaned up, does not exist on node. First available block: {}",
+                    slot, first_available_block
+                ),
+            )
+        } else {
+            RpcError::new(
+                BLOCK_NOT_AVAILABLE,
+                format!("Block not available for slot {}", slot),
+            )
+        }
+    }
+
+    fn get_account_info(&self, params: Params) -> RpcResult {
+        let pubkey = parse_pubkey_param(&params.required::<String>(0)?)?;
+        let config = params.optional_or_default::<AccountInfoConfig>(1)?;
+        let commitment = commitment_level(config.commitment);
+        let value = match self
+            .rpc
+            .get_account_info_with_commitment(&pubkey, commitment)
+        {
+            Some(account) => {
+                let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+                let parsed = decode_account(&self.rpc, &pubkey, &account, encoding);
+                encode_account(&account, encoding, config.data_slice, parsed)?
+            }
+            None => Value::Null,
+        };
+
+        Ok(self.with_commitment_context(commitment, value))
+    }
+
+    fn get_multiple_accounts(&self, params: Params) -> RpcResult {
+        let pubkeys = params.required::<Vec<String>>(0)?;
+        if pubkeys.len() > MAX_MULTIPLE_ACCOUNTS {
+            return Err(RpcError::invalid_params(format!(
+                "Too many inputs provided; max {}",
+                MAX_MULTIPLE_ACCOUNTS
+            )));
+        }
+        let pubkeys = pubkeys
+            .iter()
+            .map(|pubkey| parse_pubkey_param(pubkey))
+            .collect::<RpcResult<Vec<_>>>()?;
+        let config = params.optional_or_default::<AccountInfoConfig>(1)?;
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
+        let commitment = commitment_level(config.commitment);
+
+        let accounts = self
+            .rpc
+            .get_multiple_accounts_with_commitment(&pubkeys, commitment)
+            .iter()
+            .zip(&pubkeys)
+            .map(|(account, pubkey)| match account {
+                Some(account) => {
+                    let parsed = decode_account(&self.rpc, pubkey, account, encoding);
+                    encode_account(account, encoding, config.data_slice, parsed)
+                }
+                None => Ok(Value::Null),
+            })
+            .collect::<RpcResult<Vec<_>>>()?;
+
+        Ok(self.with_commitment_context(commitment, json!(accounts)))
+    }
+
+    fn get_program_accounts(&self, params: Params) -> RpcResult {
+        let program_id = parse_pubkey_param(&params.required::<String>(0)?)?;
+        let config = params.optional_or_default::<ProgramAccountsConfig>(1)?;
+        let filters = parse_filters(config.filters.unwrap_or_default())?;
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+
+        let accounts = self
+            .rpc
+            .get_program_accounts(&program_id, &filters)
+            .iter()
+            .map(|(pubkey, account)| {
+                let parsed = decode_account(&self.rpc, pubkey, account, encoding);
+                Ok(json!({
+                    "pubkey": pubkey.to_string(),
+                    "account": encode_account(account, encoding, config.data_slice, parsed)?,
+                }))
+            })
+            .collect::<RpcResult<Vec<_>>>()?;
+
+        Ok(if config.with_context {
+            self.with_context(json!(accounts))
+        } else {
+            json!(accounts)
+        })
+    }
+
+    fn get_fee_for_message(&self, params: Params) -> RpcResult {
+        let wire_msg = base64::decode(params.required::<String>(0)?).map_err(|err| {
+            RpcError::invalid_params(format!("invalid base64 encoding: {:?}", err))
+        })?;
+        let msg = bincode::deserialize::<VersionedMessage>(&wire_msg).map_err(|err| {
+            RpcError::invalid_params(format!("invalid transaction message: {}", err))
+        })?;
+        let fee = self.rpc.get_fee_for_message(msg).map_err(into_rpc_error)?;
+
+        Ok(self.with_context(json!(fee)))
+    }
+
+    fn get_signature_statuses(&self, params: Params) -> RpcResult {
+        let signatures = params.required::<Vec<String>>(0)?;
+        if signatures.len() > MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS {
+            return Err(RpcError::invalid_params(format!(
+                "Too many inputs provided; max {}",
+                MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS
+            )));
+        }
+        let signatures = signatures
+            .iter()
+            .map(|signature| parse_signature_param(signature))
+            .collect::<RpcResult<Vec<_>>>()?;
+        let config = params.optional_or_default::<SignatureStatusConfig>(1)?;
+
+        let statuses = self
+            .rpc
+            .get_signature_statuses(&signatures, config.search_transaction_history)
+            .into_iter()
+            .map(|status| match status {
+                Some(status) => {
+                    let confirmation_status = status.confirmation_status.map(encode_commitment);
+                    // Finalized transactions don't have confirmations
+                    let confirmations = match status.confirmation_status {
+                        Some(CommitmentLevel::Finalized) => None,
+                        _ => status.confirmations,
+                    };
+
+                    json!({
+                        "slot": status.slot,
+                        "confirmations": confirmations,
+                        "err": status.err,
+                        "status": encode_status(&status.err),
+                        "confirmationStatus": confirmation_status,
+                    })
+                }
+                None => Value::Null,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(self.with_context(json!(statuses)))
+    }
+
+    fn get_signatures_for_address(&self, params: Params) -> RpcResult {
+        let address = parse_pubkey_param(&params.required::<String>(0)?)?;
+        let config = params.optional_or_default::<SignaturesForAddressConfig>(1)?;
+        let before = config
+            .before
+            .as_deref()
+            .map(parse_signature_param)
+            .transpose()?;
+        let until = config
+            .until
+            .as_deref()
+            .map(parse_signature_param)
+            .transpose()?;
+        let limit = config
+            .limit
+            .unwrap_or(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT);
+        if limit == 0 || limit > MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT {
+            return Err(RpcError::invalid_params(format!(
+                "Invalid limit; max {}",
+                MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT
+            )));
+        }
+        let commitment = confirmed_commitment_level(config.commitment)?;
+
+        let infos = self
+            .rpc
+            .get_signatures_for_address(
+                &address,
+                before.as_ref(),
+                until.as_ref(),
+                limit,
+                commitment,
+            )
+            .into_iter()
+            .map(|info| {
+                json!({
+                    "signature": info.signature.to_string(),
+                    "slot": info.slot,
+                    "err": info.err,
+                    "memo": info.memo,
+                    "blockTime": info.block_time,
+                    "confirmationStatus": info.confirmation_status.map(encode_commitment),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!(infos))
+    }
+
+    fn get_transaction(&self, params: Params) -> RpcResult {
+        let signature = parse_signature_param(&params.required::<String>(0)?)?;
+        let config = match params.optional::<TransactionConfigWrapper>(1)? {
+            Some(TransactionConfigWrapper::Encoding(encoding)) => TransactionConfig {
+                encoding: Some(encoding),
+                ..Default::default()
+            },
+            Some(TransactionConfigWrapper::Config(config)) => config,
+            None => TransactionConfig::default(),
+        };
+
+        let commitment = confirmed_commitment_level(config.commitment)?;
+
+        let tx_data = match self
+            .rpc
+            .get_transaction_with_commitment(&signature, commitment)
+        {
+            Some(tx_data) => tx_data,
+            None => return Ok(Value::Null),
+        };
+
+        let mut value = encode_transaction_with_meta(
+            &tx_data,
+            config.encoding.unwrap_or(UiTransactionEncoding::Json),
+            config.max_supported_transaction_version,
+        )?;
+        value["slot"] = json!(tx_data.get_slot());
+        value["blockTime"] = json!(tx_data.get_block_time());
+
+        Ok(value)
+    }
+
+    fn get_block(&self, params: Params) -> RpcResult {
+        let slot = params.required::<Slot>(0)?;
+        let config = match params.optional::<BlockConfigWrapper>(1)? {
+            Some(BlockConfigWrapper::Encoding(encoding)) => BlockConfig {
+                encoding: Some(encoding),
+                ..Default::default()
+            },
+            Some(BlockConfigWrapper::Config(config)) => config,
+            None => BlockConfig::default(),
+        };
+
+        let confirmed_block = match self.rpc.get_block(slot) {
+            Some(confirmed_block) => confirmed_block,
+            None => return Err(self.block_error(slot)),
+        };
+        let block = &confirmed_block.block;
+        let mut value = json!({
+            "blockhash": block.blockhash.to_string(),
+            "previousBlockhash": block.previous_blockhash.to_string(),
+            "parentSlot": block.parent_slot,
+            "blockTime": block.block_time,
+            "blockHeight": block.block_height,
+        });
+        match config.transaction_details.unwrap_or_default() {
+            TransactionDetails::Full => {
+                let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
+                value["transactions"] = confirmed_block
+                    .transactions
+                    .iter()
+                    .map(|tx_data| {
+                        encode_transaction_with_meta(
+                            tx_data,
+                            encoding,
+                            config.max_supported_transaction_version,
+                        )
+                    })
+                    .collect::<RpcResult<_>>()?;
+            }
+            TransactionDetails::Signatures => {
+                value["signatures"] = block
+                    .signatures
+                    .iter()
+                    .map(|signature| json!(signature.to_string()))
+                    .collect();
+            }
+            TransactionDetails::None => {}
+        }
+        // Playnet doesn't pay rewards
+        if config.rewards.unwrap_or(true) {
+            value["rewards"] = json!([]);
+        }
+
+        Ok(value)
+    }
+
+    fn request_airdrop(&self, params: Params) -> RpcResult {
+        let pubkey = parse_pubkey_param(&params.required::<String>(0)?)?;
+        let lamports = params.required::<u64>(1)?;
+        let signature = self
+            .rpc
+            .request_airdrop(&pubkey, lamports)
+            .map_err(|err| RpcError::internal_error(format!("Airdrop failed: {}", err)))?;
+
+        Ok(json!(signature.to_string()))
+    }
+
+    fn send_transaction(&self, params: Params) -> RpcResult {
+        let config = params.optional_or_default::<SendTransactionConfig>(1)?;
+        let tx = self.get_sanitized_tx(
+            &params.required::<String>(0)?,
+            config.encoding.unwrap_or(UiTransactionEncoding::Base58),
+        )?;
+        let signature = *tx.signature();
+
+        if !config.skip_preflight {
+            self.rpc.verify_transaction(&tx).map_err(into_rpc_error)?;
+
+            let result = self.rpc.simulate_transaction(&tx);
+            if let Err(err) = &result.result {
+                return Err(preflight_failure(err, encode_simulation_result(&result)));
+            }
+        }
+
+        match self.rpc.send_transaction(tx) {
+            Ok(_) => Ok(json!(signature.to_string())),
+            Err(PlaynetError::Transaction(TransactionError::SignatureFailure)) => {
+                Err(signature_verification_failure())
+            }
+            Err(PlaynetError::Transaction(err)) => Err(preflight_failure(
+                &err,
+                encode_simulation_result(&SimulateTransactionResult::new_error(err.clone())),
+            )),
+            Err(err) => Err(into_rpc_error(err)),
+        }
+    }
+
+    fn simulate_transaction(&self, params: Params) -> RpcResult {
+        let config = params.optional_or_default::<SimulateTransactionConfig>(1)?;
+        let tx = decode_transaction(
+            &params.required::<String>(0)?,
+            config.encoding.unwrap_or(UiTransactionEncoding::Base58),
+        )?;
+
+        let (accounts_encoding, addresses) = match config.accounts {
+            Some(SimulateTransactionAccountsConfig {
+                encoding,
+                addresses,
+            }) => {
+                let encoding = encoding.unwrap_or(UiAccountEncoding::Base64);
+                if matches!(
+                    encoding,
+                    UiAccountEncoding::Binary | UiAccountEncoding::Base58
+                ) {
+                    return Err(RpcError::invalid_params("base58 encoding not supported"));
+                }
+                let addresses = addresses
+                    .iter()
+                    .map(|address| parse_pubkey_param(address))
+                    .collect::<RpcResult<Vec<_>>>()?;
+                (encoding, Some(addresses))
+            }
+            None => (UiAccountEncoding::Base64, None),
+        };
+
+        let result = self
+            .rpc
+            .simulate_transaction_with_config(
+                tx,
+                &playnet::types::SimulateTransactionConfig {
+                    sig_verify: config.sig_verify,
+                    replace_recent_blockhash: config.replace_recent_blockhash,
+                    accounts: addresses.clone(),
+                    inner_instructions: config.inner_instructions,
+                    min_context_slot: config.min_context_slot,
+                    ..Default::default()
+                },
+            )
+            .map_err(into_sanitize_error)?;
+
+        let mut value = encode_simulation_result(&result);
+        if let (Some(addresses), Some(accounts)) = (addresses, &result.accounts) {
+            value["accounts"] = accounts
+                .iter()
+                .zip(&addresses)
+                .map(|(account, address)| match account {
+                    Some(account) => {
+                        let parsed = decode_account(&self.rpc, address, account, accounts_encoding);
+                        encode_account(account, accounts_encoding, None, parsed)
+                    }
+                    None => Ok(Value::Null),
+                })
+                .collect::<RpcResult<Vec<_>>>()?
+                .into();
+        }
+
+        Ok(self.with_context(value))
+    }
+
+    fn get_sanitized_tx(
+        &self,
+        encoded_tx: &str,
+        encoding: UiTransactionEncoding,
+    ) -> RpcResult<SanitizedTransaction> {
+        let tx = decode_transaction(encoded_tx, encoding)?;
+        self.rpc
+            .sanitize_transaction(tx)
+            .map_err(into_sanitize_error)
+    }
+}
+
+/// Encodes the transaction as `{ transaction, meta, version }`, `version` is
+/// only included if the client specified the max supported version
+fn encode_transaction_with_meta(
+    tx_data: &TransactionData,
+    encoding: UiTransactionEncoding,
+    max_supported_transaction_version: Option<u8>,
+) -> RpcResult {
+    let tx = tx_data.get_tx();
+    let version = match (tx.version(), max_supported_transaction_version) {
+        (TransactionVersion::Legacy(_), Some(_)) => Some(json!("legacy")),
+        (TransactionVersion::Legacy(_), None) => None,
+        (TransactionVersion::Number(version), Some(max_version)) if version <= max_version => {
+            Some(json!(version))
+        }
+        (TransactionVersion::Number(version), _) => {
+            return Err(RpcError::new(
+                UNSUPPORTED_TRANSACTION_VERSION,
+                format!(
+                    "Transaction version ({}) is not supported by the requesting client. \
+                    Please try the request again with the following configuration parameter: \
+                    \"maxSupportedTransactionVersion\": {}",
+                    version, version
+                ),
+            ))
+        }
+    };
+
+    let mut value = json!({
+        "transaction": encode_transaction(tx, encoding)?,
+        "meta": tx_data.get_meta().as_ref().map(encode_transaction_meta),
+    });
+    if let Some(version) = version {
+        value["version"] = version;
+    }
+
+    Ok(value)
+}
+
+/// Encodes the simulation result without the accounts, they depend on the
+/// requested encoding
+fn encode_simulation_result(result: &SimulateTransactionResult) -> Value {
+    json!({
+        "err": result.result.as_ref().err(),
+        "logs": result.logs,
+        "accounts": null,
+        "unitsConsumed": result.units_consumed,
+        "returnData": result.return_data.as_ref().map(|return_data| json!({
+            "programId": return_data.program_id.to_string(),
+            "data": [base64::encode(&return_data.data), "base64"],
+        })),
+        "innerInstructions": result
+            .inner_instructions
+            .as_ref()
+            .map(|inner_instructions| encode_inner_instructions(inner_instructions)),
+        "replacementBlockhash": result.replacement_blockhash.map(|latest_blockhash| json!({
+            "blockhash": latest_blockhash.blockhash.to_string(),
+            "lastValidBlockHeight": latest_blockhash.last_valid_block_height,
+        })),
+        "instructionComputeUnits": encode_instruction_compute_units(
+            &result.instruction_compute_units()
+        ),
+        "programDetails": encode_program_details(&result.program_details),
+    })
+}
+
+fn preflight_failure(err: &TransactionError, data: Value) -> RpcError {
+    RpcError::new(
+        SEND_TRANSACTION_PREFLIGHT_FAILURE,
+        format!("Transaction simulation failed: {}", err),
+    )
+    .with_data(data)
+}
+
+fn signature_verification_failure() -> RpcError {
+    RpcError::new(
+        TRANSACTION_SIGNATURE_VERIFICATION_FAILURE,
+        "Transaction signature verification failure",
+    )
+}
+
+/// Transaction errors before the verification are sanitization errors
+fn into_sanitize_error(err: PlaynetError) -> RpcError {
+    match err {
+        PlaynetError::Transaction(TransactionError::SignatureFailure) => into_rpc_error(err),
+        PlaynetError::Transaction(err) => {
+            RpcError::invalid_params(format!("invalid transaction: {}", err))
+        }
+        err => into_rpc_error(err),
+    }
+}
+
+fn into_rpc_error(err: PlaynetError) -> RpcError {
+    match err {
+        PlaynetError::Transaction(TransactionError::SignatureFailure) => {
+            signature_verification_failure()
+        }
+        PlaynetError::PrecompileVerificationFailure(_) => {
+            RpcError::new(TRANSACTION_PRECOMPILE_VERIFICATION_FAILURE, err.to_string())
+        }
+        PlaynetError::MinContextSlotNotReached { context_slot } => {
+            RpcError::new(MIN_CONTEXT_SLOT_NOT_REACHED, err.to_string())
+                .with_data(json!({ "contextSlot": context_slot }))
+        }
+        PlaynetError::Io(_) => RpcError::internal_error(err.to_string()),
+        _ => RpcError::invalid_params(err.to_string()),
+    }
+}
+
+pub fn parse_filters(filters: Vec<RpcFilterType>) -> RpcResult<Vec<AccountFilter>> {
+    if filters.len() > MAX_ACCOUNT_FILTERS {
+        return Err(RpcError::invalid_params(format!(
+            "Too many filters provided; max {}",
+            MAX_ACCOUNT_FILTERS
+        )));
+    }
+
+    filters
+        .into_iter()
+        .map(|filter| match filter {
+            RpcFilterType::DataSize(size) => Ok(AccountFilter::DataSize(size)),
+            RpcFilterType::Memcmp(RpcMemcmp {
+                offset,
+                bytes,
+                encoding,
+            }) => {
+                let bytes = match encoding {
+                    RpcMemcmpEncoding::Base58 => bs58::decode(bytes).into_vec().ok(),
+                    RpcMemcmpEncoding::Base64 => base64::decode(bytes).ok(),
+                }
+                .ok_or_else(|| RpcError::invalid_params("Invalid param: invalid memcmp bytes"))?;
+                if bytes.len() > MAX_MEMCMP_BYTES {
+                    return Err(RpcError::invalid_params(
+                        "Invalid param: memcmp bytes too large",
+                    ));
+                }
+
+                Ok(AccountFilter::Memcmp { offset, bytes })
+            }
+        })
+        .collect()
+}
+
+/// Decodes the account data if the `jsonParsed` encoding is requested
+pub fn decode_account(
+    rpc: &PgRpc,
+    pubkey: &Pubkey,
+    account: &Account,
+    encoding: UiAccountEncoding,
+) -> Option<ParsedAccount> {
+    match encoding {
+        UiAccountEncoding::JsonParsed => rpc.decode_account(pubkey, account),
+        _ => None,
+    }
+}
+
+/// Parses the `{ commitment }` config at `index`
+pub fn commitment_param(params: &Params, index: usize) -> RpcResult<CommitmentLevel> {
+    let config = params.optional_or_default::<CommitmentConfig>(index)?;
+    Ok(commitment_level(config.commitment))
+}
+
+/// Requests without a commitment level read the finalized state, like on the
+/// Solana RPC
+fn commitment_level(commitment: Option<RpcCommitmentLevel>) -> CommitmentLevel {
+    commitment
+        .map(CommitmentLevel::from)
+        .unwrap_or(CommitmentLevel::Finalized)
+}
+
+/// Same as `commitment_level` for the methods that only accept `confirmed`
+/// and `finalized` explicitly
+fn confirmed_commitment_level(
+    commitment: Option<RpcCommitmentLevel>,
+) -> RpcResult<CommitmentLevel> {
+    match commitment {
+        Some(RpcCommitmentLevel::Processed) => Err(RpcError::invalid_params(
+            "Method does not support commitment below `confirmed`",
+        )),
+        commitment => Ok(commitment_level(commitment)),
+    }
+}
+
+fn encode_commitment(level: CommitmentLevel) -> &'static str {
+    match level {
+        CommitmentLevel::Processed => "processed",
+        CommitmentLevel::Confirmed => "confirmed",
+        CommitmentLevel::Finalized => "finalized",
+    }
+}
+
+pub fn parse_pubkey_param(pubkey: &str) -> RpcResult<Pubkey> {
+    parse_pubkey(pubkey).map_err(into_rpc_error)
+}
+
+pub fn parse_signature_param(signature: &str) -> RpcResult<Signature> {
+    parse_signature(signature).map_err(into_rpc_error)
+}
@@ -0,0 +1,349 @@
+// Solana compatible PubSub WebSocket endpoint.
+//
+// Every connection is served by its own thread. Notifications are queued by the
+// subscription sinks and encoded and sent by the connection thread in between
+// reading the requests, since the sinks are called while the bank is locked.
+
+use {
+    crate::{
+        encoding::{encode_account, DataSlice, UiAccountEncoding},
+        jsonrpc::{handle_body, Params, RpcError, RpcResult},
+        methods::{
+            commitment_param, decode_account, parse_filters, parse_pubkey_param,
+            parse_signature_param, RpcFilterType,
+        },
+    },
+    playnet::{
+        pubsub::{LogsFilter, Notification, SubscriptionId, SubscriptionParams},
+        rpc::PgRpc,
+    },
+    serde::Deserialize,
+    serde_json::{json, Value},
+    std::{
+        cell::RefCell,
+        collections::HashMap,
+        io::{self, ErrorKind},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::{
+            mpsc::{self, Receiver, Sender},
+            Mutex,
+        },
+        thread,
+        time::Duration,
+    },
+    tracing::{debug, info},
+    tungstenite::{accept, Message, WebSocket},
+};
+
+/// How long to wait for a request before sending the queued notifications
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountSubscribeConfig {
+    encoding: Option<UiAccountEncoding>,
+    data_slice: Option<DataSlice>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgramSubscribeConfig {
+    encoding: Option<UiAccountEncoding>,
+    data_slice: Option<DataSlice>,
+    filters: Option<Vec<RpcFilterType>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RpcLogsFilter {
+    All,
+    AllWithVotes,
+    Mentions(Vec<String>),
+}
+
+/// Starts accepting the WebSocket connections in the background
+pub fn start_pubsub_server(bind: SocketAddr, rpc: PgRpc) -> io::Result<()> {
+    let listener = TcpListener::bind(bind)?;
+    info!(%bind, "PubSub server started");
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let rpc = rpc.clone();
+                    thread::spawn(move || serve_connection(stream, rpc));
+                }
+                Err(err) => debug!(%err, "Failed to accept connection"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn serve_connection(stream: TcpStream, rpc: PgRpc) {
+    let mut websocket = match accept(stream) {
+        Ok(websocket) => websocket,
+        Err(err) => {
+            debug!(%err, "WebSocket handshake failed");
+            return;
+        }
+    };
+    if let Err(err) = websocket.get_ref().set_read_timeout(Some(POLL_INTERVAL)) {
+        debug!(%err, "Failed to set the read timeout");
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let connection = Connection {
+        rpc,
+        sender: Mutex::new(sender),
+        subscriptions: RefCell::new(HashMap::new()),
+    };
+    connection.run(&mut websocket, receiver);
+
+    // Clean up the subscriptions of the connection
+    for id in connection.subscriptions.take().into_keys() {
+        connection.rpc.unsubscribe(id);
+    }
+}
+
+/// Encodes the account notifications, the rest of the notifications have a
+/// single representation
+type EncodeValue = Box<dyn Fn(&Notification) -> Option<Value>>;
+
+struct Subscription {
+    method: &'static str,
+    encode_value: EncodeValue,
+}
+
+struct Connection {
+    rpc: PgRpc,
+    /// Sinks are shared between threads and `Sender` is not `Sync`
+    sender: Mutex<Sender<(SubscriptionId, Notification)>>,
+    subscriptions: RefCell<HashMap<SubscriptionId, Subscription>>,
+}
+
+impl Connection {
+    /// Serves the connection until it's closed
+    fn run(
+        &self,
+        websocket: &mut WebSocket<TcpStream>,
+        receiver: Receiver<(SubscriptionId, Notification)>,
+    ) {
+        loop {
+            match websocket.read_message() {
+                Ok(Message::Text(request)) => {
+                    let response = handle_body(request.as_bytes(), |method, params| {
+                        self.handle(method, params)
+                    });
+                    if let Err(err) = websocket.write_message(Message::Text(response.to_string())) {
+                        debug!(%err, "Failed to send the response");
+                        return;
+                    }
+                }
+                Ok(Message::Close(_)) => return,
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(err) => {
+                    debug!(%err, "WebSocket connection closed");
+                    return;
+                }
+            }
+
+            for (id, notification) in receiver.try_iter() {
+                let notification = match self.encode_notification(id, notification) {
+                    Some(notification) => notification,
+                    None => continue,
+                };
+                if let Err(err) = websocket.write_message(Message::Text(notification)) {
+                    debug!(%err, "Failed to send the notification");
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handle(&self, method: &str, params: Params) -> RpcResult {
+        match method {
+            "accountSubscribe" => self.account_subscribe(params),
+            "programSubscribe" => self.program_subscribe(params),
+            "signatureSubscribe" => {
+                let signature = parse_signature_param(&params.required::<String>(0)?)?;
+                let commitment = commitment_param(&params, 1)?;
+                Ok(self.subscribe(
+                    SubscriptionParams::Signature {
+                        signature,
+                        commitment,
+                    },
+                    "signatureNotification",
+                    Box::new(|_| None),
+                ))
+            }
+            "logsSubscribe" => self.logs_subscribe(params),
+            "slotSubscribe" => Ok(self.subscribe(
+                SubscriptionParams::Slot,
+                "slotNotification",
+                Box::new(|_| None),
+            )),
+            "accountUnsubscribe"
+            | "programUnsubscribe"
+            | "signatureUnsubscribe"
+            | "logsUnsubscribe"
+            | "slotUnsubscribe" => {
+                let id = params.required::<SubscriptionId>(0)?;
+                // Only the subscriptions of the connection can be removed
+                if self.subscriptions.borrow_mut().remove(&id).is_some() {
+                    self.rpc.unsubscribe(id);
+                    Ok(json!(true))
+                } else {
+                    Err(RpcError::invalid_params("Invalid subscription id."))
+                }
+            }
+            _ => Err(RpcError::method_not_found()),
+        }
+    }
+
+    fn account_subscribe(&self, params: Params) -> RpcResult {
+        let pubkey = parse_pubkey_param(&params.required::<String>(0)?)?;
+        let config = params.optional_or_default::<AccountSubscribeConfig>(1)?;
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let data_slice = config.data_slice;
+        let rpc = self.rpc.clone();
+
+        Ok(self.subscribe(
+            SubscriptionParams::Account(pubkey),
+            "accountNotification",
+            Box::new(move |notification| match notification {
+                Notification::Account { account, .. } => {
+                    let parsed = decode_account(&rpc, &pubkey, account, encoding);
+                    encode_account(account, encoding, data_slice, parsed).ok()
+                }
+                _ => None,
+            }),
+        ))
+    }
+
+    fn program_subscribe(&self, params: Params) -> RpcResult {
+        let program_id = parse_pubkey_param(&params.required::<String>(0)?)?;
+        let config = params.optional_or_default::<ProgramSubscribeConfig>(1)?;
+        let filters = parse_filters(config.filters.unwrap_or_default())?;
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let data_slice = config.data_slice;
+        let rpc = self.rpc.clone();
+
+        Ok(self.subscribe(
+            SubscriptionParams::Program {
+                program_id,
+                filters,
+            },
+            "programNotification",
+            Box::new(move |notification| match notification {
+                Notification::Program {
+                    pubkey, account, ..
+                } => {
+                    let parsed = decode_account(&rpc, pubkey, account, encoding);
+                    encode_account(account, encoding, data_slice, parsed)
+                        .ok()
+                        .map(|account| json!({ "pubkey": pubkey.to_string(), "account": account }))
+                }
+                _ => None,
+            }),
+        ))
+    }
+
+    fn logs_subscribe(&self, params: Params) -> RpcResult {
+        let filter = match params.required::<RpcLogsFilter>(0)? {
+            RpcLogsFilter::All | RpcLogsFilter::AllWithVotes => LogsFilter::All,
+            RpcLogsFilter::Mentions(mentions) => match mentions.as_slice() {
+                [pubkey] => LogsFilter::Mentions(parse_pubkey_param(pubkey)?),
+                _ => {
+                    return Err(RpcError::invalid_params(
+                        "Invalid Request: Only 1 address supported",
+                    ))
+                }
+            },
+        };
+
+        Ok(self.subscribe(
+            SubscriptionParams::Logs(filter),
+            "logsNotification",
+            Box::new(|_| None),
+        ))
+    }
+
+    /// Subscribes with a sink that queues the notifications for the connection
+    fn subscribe(
+        &self,
+        params: SubscriptionParams,
+        method: &'static str,
+        encode_value: EncodeValue,
+    ) -> Value {
+        let sender = Mutex::new(self.sender.lock().unwrap().clone());
+        let id = self.rpc.subscribe(
+            params,
+            Box::new(move |id, notification| {
+                // The connection might already be closed
+                sender.lock().unwrap().send((id, notification)).ok();
+            }),
+        );
+        self.subscriptions.borrow_mut().insert(
+            id,
+            Subscription {
+                method,
+                encode_value,
+            },
+        );
+
+        json!(id)
+    }
+
+    /// Returns `None` if the notification shouldn't be sent
+    fn encode_notification(
+        &self,
+        id: SubscriptionId,
+        notification: Notification,
+    ) -> Option<String> {
+        let mut subscriptions = self.subscriptions.borrow_mut();
+        let subscription = subscriptions.get(&id)?;
+        let result = match &notification {
+            Notification::Account { slot, .. } | Notification::Program { slot, .. } => {
+                with_slot(*slot, (subscription.encode_value)(&notification)?)
+            }
+            Notification::Signature { err, slot } => {
+                // Signature subscriptions end after the first notification
+                let result = with_slot(*slot, json!({ "err": err }));
+                let subscription = subscriptions.remove(&id)?;
+                return Some(format_notification(subscription.method, id, result));
+            }
+            Notification::Logs {
+                signature,
+                err,
+                logs,
+                slot,
+            } => with_slot(
+                *slot,
+                json!({ "signature": signature.to_string(), "err": err, "logs": logs }),
+            ),
+            Notification::Slot { slot, parent, root } => {
+                json!({ "slot": slot, "parent": parent, "root": root })
+            }
+        };
+
+        Some(format_notification(subscription.method, id, result))
+    }
+}
+
+fn format_notification(method: &str, id: SubscriptionId, result: Value) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": { "result": result, "subscription": id },
+    })
+    .to_string()
+}
+
+fn with_slot(slot: u64, value: Value) -> Value {
+    json!({ "context": { "slot": slot }, "value": value })
+}
@@ -15,9 +15,15 @@ use {
         signature::{Keypair, Signer},
         transaction::{SanitizedTransaction, Transaction},
     },
+    tracing_subscriber::EnvFilter,
 };
 
 fn main() {
+    // Runtime logs are silent unless enabled with e.g. `RUST_LOG=playnet=trace`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
     println!("Hello, world!");
 
     let GenesisConfigInfo {
@@ -53,11 +59,7 @@ fn main() {
         ),
     );
 
-    let mut bank = PgBank::new(None);
-    for (pubkey, account) in genesis_config.accounts.iter() {
-        println!("pk: {:?}", pubkey);
-        bank.add_account(pubkey, account);
-    }
+    let mut bank = PgBank::from_genesis_config(&genesis_config, None);
     bank.add_builtin(
         "mock_program",
         &mock_program_id,